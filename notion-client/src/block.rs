@@ -0,0 +1,533 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rich_text::{Color, Equation, LinkPreview, RichText};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Block {
+    pub id: String,
+    pub has_children: bool,
+    #[serde(flatten)]
+    pub block: BlockType,
+}
+
+/// The text of paragraphs, headings, list items, quotes and toggles.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Text {
+    pub rich_text: Vec<RichText>,
+    #[serde(default)]
+    pub color: Color,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Heading {
+    pub rich_text: Vec<RichText>,
+    #[serde(default)]
+    pub color: Color,
+    /// Whether the heading holds its children collapsed beneath it.
+    #[serde(default)]
+    pub is_toggleable: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToDo {
+    pub rich_text: Vec<RichText>,
+    pub checked: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Icon {
+    Emoji {
+        emoji: String,
+    },
+    External {
+        external: ExternalFile,
+    },
+    File {
+        file: HostedFile,
+    },
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Callout {
+    pub rich_text: Vec<RichText>,
+    pub icon: Option<Icon>,
+}
+
+/// The languages Notion highlights code blocks as, named as in the API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeLanguage {
+    Abap,
+    Agda,
+    Arduino,
+    #[serde(rename = "ascii art")]
+    AsciiArt,
+    Assembly,
+    Bash,
+    Basic,
+    Bnf,
+    C,
+    #[serde(rename = "c#")]
+    CSharp,
+    #[serde(rename = "c++")]
+    CPlusPlus,
+    Clojure,
+    CoffeeScript,
+    Coq,
+    Css,
+    Dart,
+    Dhall,
+    Diff,
+    Docker,
+    Ebnf,
+    Elixir,
+    Elm,
+    Erlang,
+    #[serde(rename = "f#")]
+    FSharp,
+    Flow,
+    Fortran,
+    Gherkin,
+    Glsl,
+    Go,
+    GraphQL,
+    Groovy,
+    Haskell,
+    Hcl,
+    Html,
+    Idris,
+    Java,
+    JavaScript,
+    Json,
+    Julia,
+    Kotlin,
+    LaTeX,
+    Less,
+    Lisp,
+    LiveScript,
+    #[serde(rename = "llvm ir")]
+    LlvmIr,
+    Lua,
+    Makefile,
+    Markdown,
+    Markup,
+    Matlab,
+    Mathematica,
+    Mermaid,
+    Nix,
+    #[serde(rename = "notion formula")]
+    NotionFormula,
+    #[serde(rename = "objective-c")]
+    ObjectiveC,
+    OCaml,
+    Pascal,
+    Perl,
+    Php,
+    #[serde(rename = "plain text")]
+    PlainText,
+    PowerShell,
+    Prolog,
+    Protobuf,
+    PureScript,
+    Python,
+    R,
+    Racket,
+    Reason,
+    Ruby,
+    Rust,
+    Sass,
+    Scala,
+    Scheme,
+    Scss,
+    Shell,
+    Smalltalk,
+    Solidity,
+    Sql,
+    Swift,
+    Toml,
+    TypeScript,
+    #[serde(rename = "vb.net")]
+    VbNet,
+    Verilog,
+    Vhdl,
+    #[serde(rename = "visual basic")]
+    VisualBasic,
+    WebAssembly,
+    Xml,
+    Yaml,
+    #[serde(rename = "java/c/c++/c#")]
+    JavaCCPlusPlusCSharp,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Code {
+    pub rich_text: Vec<RichText>,
+    #[serde(default)]
+    pub caption: Vec<RichText>,
+    pub language: CodeLanguage,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExternalFile {
+    pub url: String,
+}
+
+/// A file uploaded to Notion, behind a signed URL that expires after an hour.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HostedFile {
+    pub url: String,
+    pub expiry_time: String,
+}
+
+/// The file of an image, video, file or PDF block.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum File {
+    External {
+        external: ExternalFile,
+        #[serde(default)]
+        caption: Vec<RichText>,
+    },
+    File {
+        file: HostedFile,
+        #[serde(default)]
+        caption: Vec<RichText>,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Bookmark {
+    pub url: String,
+    #[serde(default)]
+    pub caption: Vec<RichText>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChildPage {
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChildDatabase {
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LinkToPage {
+    #[serde(rename = "page_id")]
+    Page { page_id: String },
+    #[serde(rename = "database_id")]
+    Database { database_id: String },
+    /// Links to comments, which have nothing to show outside of Notion.
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SyncedFrom {
+    pub block_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SyncedBlock {
+    /// The original block this is a copy of, or `None` for the original,
+    /// which holds the content as its children.
+    pub synced_from: Option<SyncedFrom>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Table {
+    pub table_width: usize,
+    pub has_column_header: bool,
+    pub has_row_header: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TableRow {
+    pub cells: Vec<Vec<RichText>>,
+}
+
+/// The type of a block along with its content. Block types that can't be read
+/// through the API, or are newer than this client, are [`BlockType::Unsupported`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BlockType {
+    Paragraph {
+        paragraph: Text,
+    },
+    #[serde(rename = "heading_1")]
+    Heading1 {
+        #[serde(rename = "heading_1")]
+        heading: Heading,
+    },
+    #[serde(rename = "heading_2")]
+    Heading2 {
+        #[serde(rename = "heading_2")]
+        heading: Heading,
+    },
+    #[serde(rename = "heading_3")]
+    Heading3 {
+        #[serde(rename = "heading_3")]
+        heading: Heading,
+    },
+    Callout {
+        callout: Callout,
+    },
+    Quote {
+        quote: Text,
+    },
+    BulletedListItem {
+        bulleted_list_item: Text,
+    },
+    NumberedListItem {
+        numbered_list_item: Text,
+    },
+    ToDo {
+        to_do: ToDo,
+    },
+    Toggle {
+        toggle: Text,
+    },
+    Code {
+        code: Code,
+    },
+    ChildPage {
+        child_page: ChildPage,
+    },
+    ChildDatabase {
+        child_database: ChildDatabase,
+    },
+    Embed {
+        embed: Bookmark,
+    },
+    Image {
+        image: File,
+    },
+    Video {
+        video: File,
+    },
+    File {
+        file: File,
+    },
+    Pdf {
+        pdf: File,
+    },
+    Bookmark {
+        bookmark: Bookmark,
+    },
+    Equation {
+        equation: Equation,
+    },
+    Divider,
+    TableOfContents,
+    Breadcrumb,
+    ColumnList {},
+    Column {},
+    LinkPreview {
+        link_preview: LinkPreview,
+    },
+    Template,
+    LinkToPage {
+        link_to_page: LinkToPage,
+    },
+    SyncedBlock {
+        synced_block: SyncedBlock,
+    },
+    Table {
+        table: Table,
+    },
+    TableRow {
+        table_row: TableRow,
+    },
+    #[serde(other)]
+    Unsupported,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(json: serde_json::Value) -> Block {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn text(content: &str) -> serde_json::Value {
+        serde_json::json!({
+            "type": "text",
+            "text": { "content": content, "link": null },
+            "annotations": {
+                "bold": true,
+                "italic": false,
+                "strikethrough": false,
+                "underline": false,
+                "code": false,
+                "color": "red_background"
+            },
+            "plain_text": content,
+            "href": null
+        })
+    }
+
+    #[test]
+    fn headings() {
+        let block = block(serde_json::json!({
+            "object": "block",
+            "id": "a",
+            "has_children": true,
+            "type": "heading_2",
+            "heading_2": { "rich_text": [text("Setup")], "color": "default", "is_toggleable": true }
+        }));
+
+        let BlockType::Heading2 { heading } = block.block else {
+            panic!("not a heading: {block:?}");
+        };
+        assert!(block.has_children);
+        assert!(heading.is_toggleable);
+        assert!(matches!(
+            &heading.rich_text[0],
+            RichText::Text { text, annotations, .. }
+                if text.content == "Setup" && annotations.bold && annotations.color == Color::RedBackground
+        ));
+    }
+
+    #[test]
+    fn toggles_and_tables() {
+        let toggle = block(serde_json::json!({
+            "id": "a",
+            "has_children": true,
+            "type": "toggle",
+            "toggle": { "rich_text": [text("More")], "color": "default" }
+        }));
+        assert!(
+            matches!(toggle.block, BlockType::Toggle { toggle } if toggle.rich_text.len() == 1)
+        );
+
+        let table = block(serde_json::json!({
+            "id": "b",
+            "has_children": true,
+            "type": "table",
+            "table": { "table_width": 2, "has_column_header": true, "has_row_header": false }
+        }));
+        assert!(matches!(
+            table.block,
+            BlockType::Table { table } if table.has_column_header && !table.has_row_header
+        ));
+
+        let row = block(serde_json::json!({
+            "id": "c",
+            "has_children": false,
+            "type": "table_row",
+            "table_row": { "cells": [[text("a")], []] }
+        }));
+        assert!(matches!(
+            row.block,
+            BlockType::TableRow { table_row } if table_row.cells.len() == 2 && table_row.cells[1].is_empty()
+        ));
+    }
+
+    #[test]
+    fn synced_blocks() {
+        let original = block(serde_json::json!({
+            "id": "a",
+            "has_children": true,
+            "type": "synced_block",
+            "synced_block": { "synced_from": null }
+        }));
+        assert!(matches!(
+            original.block,
+            BlockType::SyncedBlock { synced_block } if synced_block.synced_from.is_none()
+        ));
+
+        let copy = block(serde_json::json!({
+            "id": "b",
+            "has_children": true,
+            "type": "synced_block",
+            "synced_block": { "synced_from": { "type": "block_id", "block_id": "a" } }
+        }));
+        assert!(matches!(
+            copy.block,
+            BlockType::SyncedBlock { synced_block }
+                if synced_block.synced_from.as_ref().is_some_and(|from| from.block_id == "a")
+        ));
+    }
+
+    #[test]
+    fn files_and_links() {
+        let image = block(serde_json::json!({
+            "id": "a",
+            "has_children": false,
+            "type": "image",
+            "image": {
+                "caption": [text("A cat")],
+                "type": "file",
+                "file": { "url": "https://files.example/cat.png", "expiry_time": "2024-01-01T00:00:00.000Z" }
+            }
+        }));
+        assert!(matches!(
+            image.block,
+            BlockType::Image { image: File::File { file, caption } }
+                if file.url.ends_with("cat.png") && caption.len() == 1
+        ));
+
+        let link = block(serde_json::json!({
+            "id": "b",
+            "has_children": false,
+            "type": "link_to_page",
+            "link_to_page": { "type": "page_id", "page_id": "c" }
+        }));
+        assert!(matches!(
+            link.block,
+            BlockType::LinkToPage { link_to_page: LinkToPage::Page { page_id } } if page_id == "c"
+        ));
+
+        let comment = block(serde_json::json!({
+            "id": "d",
+            "has_children": false,
+            "type": "link_to_page",
+            "link_to_page": { "type": "comment_id", "comment_id": "e" }
+        }));
+        assert!(matches!(
+            comment.block,
+            BlockType::LinkToPage {
+                link_to_page: LinkToPage::Unsupported
+            }
+        ));
+    }
+
+    #[test]
+    fn code_languages() {
+        let code = block(serde_json::json!({
+            "id": "a",
+            "has_children": false,
+            "type": "code",
+            "code": { "rich_text": [text("int x;")], "caption": [], "language": "c++" }
+        }));
+        assert!(matches!(
+            code.block,
+            BlockType::Code { code } if code.language == CodeLanguage::CPlusPlus
+        ));
+    }
+
+    #[test]
+    fn unknown_blocks_are_unsupported() {
+        let divider = block(serde_json::json!({
+            "id": "a",
+            "has_children": false,
+            "type": "divider",
+            "divider": {}
+        }));
+        assert!(matches!(divider.block, BlockType::Divider));
+
+        let unknown = block(serde_json::json!({
+            "id": "b",
+            "has_children": false,
+            "type": "audio",
+            "audio": { "type": "external", "external": { "url": "https://example.com/a.mp3" } }
+        }));
+        assert!(matches!(unknown.block, BlockType::Unsupported));
+    }
+}
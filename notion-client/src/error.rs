@@ -0,0 +1,90 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+/// The `code` of an error response, telling apart failures that are worth
+/// handling from ones that aren't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidJson,
+    InvalidRequestUrl,
+    InvalidRequest,
+    ValidationError,
+    MissingVersion,
+    Unauthorized,
+    RestrictedResource,
+    /// The object doesn't exist, or isn't shared with the integration.
+    ObjectNotFound,
+    ConflictError,
+    RateLimited,
+    InternalServerError,
+    ServiceUnavailable,
+    DatabaseConnectionUnavailable,
+    GatewayTimeout,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The request couldn't be sent, or its response couldn't be read.
+    Http(reqwest::Error),
+    /// Notion answered the request with an error.
+    Api {
+        status: u16,
+        code: ErrorCode,
+        message: String,
+    },
+}
+
+impl Error {
+    /// The code Notion answered with, or `None` when the request didn't get
+    /// an answer.
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            Error::Http(_) => None,
+            Error::Api { code, .. } => Some(*code),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Http(source) => write!(f, "request failed: {source}"),
+            Error::Api {
+                status, message, ..
+            } => write!(f, "Notion answered with {status}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Http(source) => Some(source),
+            Error::Api { .. } => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(source: reqwest::Error) -> Self {
+        Error::Http(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_codes() {
+        let code = |code: &str| serde_json::from_value::<ErrorCode>(code.into()).unwrap();
+
+        assert_eq!(code("object_not_found"), ErrorCode::ObjectNotFound);
+        assert_eq!(code("rate_limited"), ErrorCode::RateLimited);
+        assert_eq!(code("some_new_code"), ErrorCode::Other);
+    }
+}
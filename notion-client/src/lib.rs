@@ -0,0 +1,189 @@
+//! A client for the parts of the Notion API needed to read pages: listing the
+//! children of blocks, retrieving pages and querying databases.
+
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+mod block;
+mod error;
+mod page;
+mod rich_text;
+
+pub use block::*;
+pub use error::{Error, ErrorCode};
+pub use page::*;
+pub use rich_text::*;
+
+const API_URL: &str = "https://api.notion.com/v1";
+const NOTION_VERSION: &str = "2022-06-28";
+/// The most results Notion returns at once.
+const PAGE_SIZE: u32 = 100;
+
+/// One page of results from an endpoint that returns a list. When `has_more`
+/// is set, the rest is fetched by passing `next_cursor` as the `start_cursor`
+/// of the next request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct List<T> {
+    pub results: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+struct Http {
+    client: reqwest::Client,
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    code: ErrorCode,
+    message: String,
+}
+
+impl Http {
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, Error> {
+        let response = request
+            .bearer_auth(&self.token)
+            .header("Notion-Version", NOTION_VERSION)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response.json().await?);
+        }
+
+        // Errors from proxies in front of the API don't come with a JSON body
+        let body = response.text().await?;
+        let (code, message) = match serde_json::from_str::<ErrorResponse>(&body) {
+            Ok(error) => (error.code, error.message),
+            Err(_) => (ErrorCode::Other, body),
+        };
+
+        Err(Error::Api {
+            status: status.as_u16(),
+            code,
+            message,
+        })
+    }
+}
+
+pub struct BlockChildrenListOptions<'a> {
+    pub block_id: &'a str,
+    pub start_cursor: Option<&'a str>,
+}
+
+pub struct PageOptions<'a> {
+    pub page_id: &'a str,
+}
+
+pub struct DatabaseQueryOptions<'a> {
+    pub database_id: &'a str,
+    pub start_cursor: Option<&'a str>,
+}
+
+#[derive(Clone)]
+pub struct Blocks {
+    http: Arc<Http>,
+}
+
+impl Blocks {
+    pub fn children(&self) -> BlockChildren {
+        BlockChildren {
+            http: self.http.clone(),
+        }
+    }
+}
+
+pub struct BlockChildren {
+    http: Arc<Http>,
+}
+
+impl BlockChildren {
+    pub async fn list(&self, options: BlockChildrenListOptions<'_>) -> Result<List<Block>, Error> {
+        let mut query = vec![("page_size", PAGE_SIZE.to_string())];
+        if let Some(cursor) = options.start_cursor {
+            query.push(("start_cursor", cursor.to_string()));
+        }
+
+        let request = self
+            .http
+            .client
+            .get(format!("{API_URL}/blocks/{}/children", options.block_id))
+            .query(&query);
+
+        self.http.send(request).await
+    }
+}
+
+#[derive(Clone)]
+pub struct Pages {
+    http: Arc<Http>,
+}
+
+impl Pages {
+    pub async fn retrieve(&self, options: PageOptions<'_>) -> Result<Page, Error> {
+        let request = self
+            .http
+            .client
+            .get(format!("{API_URL}/pages/{}", options.page_id));
+
+        self.http.send(request).await
+    }
+}
+
+#[derive(Serialize)]
+struct DatabaseQuery<'a> {
+    page_size: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_cursor: Option<&'a str>,
+}
+
+#[derive(Clone)]
+pub struct Databases {
+    http: Arc<Http>,
+}
+
+impl Databases {
+    pub async fn query(&self, options: DatabaseQueryOptions<'_>) -> Result<List<Page>, Error> {
+        let request = self
+            .http
+            .client
+            .post(format!("{API_URL}/databases/{}/query", options.database_id))
+            .json(&DatabaseQuery {
+                page_size: PAGE_SIZE,
+                start_cursor: options.start_cursor,
+            });
+
+        self.http.send(request).await
+    }
+}
+
+/// A client authenticated with an integration token, grouping endpoints the
+/// way the API does: `notion.blocks.children().list(...)`, and so on.
+#[derive(Clone)]
+pub struct Client {
+    pub blocks: Blocks,
+    pub pages: Pages,
+    pub databases: Databases,
+}
+
+impl Client {
+    pub fn new(token: impl Into<String>) -> Self {
+        let http = Arc::new(Http {
+            client: reqwest::Client::new(),
+            token: token.into(),
+        });
+
+        Client {
+            blocks: Blocks { http: http.clone() },
+            pages: Pages { http: http.clone() },
+            databases: Databases { http },
+        }
+    }
+}
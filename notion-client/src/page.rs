@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::rich_text::{Color, RichText};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Page {
+    pub id: String,
+    pub url: String,
+    pub properties: HashMap<String, PropertyValue>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct User {
+    pub id: String,
+    /// Left out of users that are only referenced, rather than listed.
+    pub name: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+/// A date or date range, each written as an ISO 8601 date or date and time.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Date {
+    pub start: String,
+    pub end: Option<String>,
+    pub time_zone: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SelectOption {
+    pub id: Option<String>,
+    pub name: String,
+    pub color: Option<Color>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FormulaResultValue {
+    String { string: Option<String> },
+    Number { number: Option<f64> },
+    Boolean { boolean: Option<bool> },
+    Date { date: Option<Date> },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PropertyValue {
+    Title {
+        id: String,
+        title: Vec<RichText>,
+    },
+    RichText {
+        id: String,
+        rich_text: Vec<RichText>,
+    },
+    Number {
+        id: String,
+        number: Option<f64>,
+    },
+    Select {
+        id: String,
+        select: Option<SelectOption>,
+    },
+    Status {
+        id: String,
+        status: Option<SelectOption>,
+    },
+    MultiSelect {
+        id: String,
+        multi_select: Vec<SelectOption>,
+    },
+    Date {
+        id: String,
+        date: Option<Date>,
+    },
+    Formula {
+        id: String,
+        formula: FormulaResultValue,
+    },
+    People {
+        id: String,
+        people: Vec<User>,
+    },
+    Checkbox {
+        id: String,
+        checkbox: bool,
+    },
+    Url {
+        id: String,
+        url: Option<String>,
+    },
+    Email {
+        id: String,
+        email: Option<String>,
+    },
+    PhoneNumber {
+        id: String,
+        phone_number: Option<String>,
+    },
+    CreatedTime {
+        id: String,
+        created_time: String,
+    },
+    CreatedBy {
+        id: String,
+        created_by: User,
+    },
+    LastEditedTime {
+        id: String,
+        last_edited_time: String,
+    },
+    LastEditedBy {
+        id: String,
+        last_edited_by: User,
+    },
+    /// Relations, rollups, files and other kinds of properties, whose values
+    /// aren't read.
+    #[serde(other)]
+    Unsupported,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn database_rows() {
+        let page: Page = serde_json::from_value(serde_json::json!({
+            "object": "page",
+            "id": "a",
+            "url": "https://www.notion.so/a",
+            "properties": {
+                "Name": {
+                    "id": "title",
+                    "type": "title",
+                    "title": [{
+                        "type": "text",
+                        "text": { "content": "Launch", "link": null },
+                        "annotations": {
+                            "bold": false, "italic": false, "strikethrough": false,
+                            "underline": false, "code": false, "color": "default"
+                        },
+                        "plain_text": "Launch",
+                        "href": null
+                    }]
+                },
+                "Stage": {
+                    "id": "s",
+                    "type": "status",
+                    "status": { "id": "1", "name": "Done", "color": "green" }
+                },
+                "Score": {
+                    "id": "f",
+                    "type": "formula",
+                    "formula": { "type": "number", "number": 4.5 }
+                },
+                "Owner": {
+                    "id": "c",
+                    "type": "created_by",
+                    "created_by": { "object": "user", "id": "u" }
+                },
+                "Related": {
+                    "id": "r",
+                    "type": "relation",
+                    "relation": [{ "id": "b" }],
+                    "has_more": false
+                }
+            }
+        }))
+        .unwrap();
+
+        let property = |name: &str| &page.properties[name];
+
+        assert!(matches!(property("Name"), PropertyValue::Title { title, .. } if title.len() == 1));
+        assert!(matches!(
+            property("Stage"),
+            PropertyValue::Status { status: Some(status), .. } if status.name == "Done"
+        ));
+        assert!(matches!(
+            property("Score"),
+            PropertyValue::Formula { formula: FormulaResultValue::Number { number: Some(number) }, .. }
+                if *number == 4.5
+        ));
+        assert!(matches!(
+            property("Owner"),
+            PropertyValue::CreatedBy { created_by, .. } if created_by.name.is_none()
+        ));
+        assert!(matches!(property("Related"), PropertyValue::Unsupported));
+    }
+}
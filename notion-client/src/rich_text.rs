@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use crate::page::{Date, User};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Color {
+    #[default]
+    Default,
+    Gray,
+    Brown,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+    Pink,
+    Red,
+    GrayBackground,
+    BrownBackground,
+    OrangeBackground,
+    YellowBackground,
+    GreenBackground,
+    BlueBackground,
+    PurpleBackground,
+    PinkBackground,
+    RedBackground,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Annotations {
+    pub bold: bool,
+    pub italic: bool,
+    pub strikethrough: bool,
+    pub underline: bool,
+    pub code: bool,
+    pub color: Color,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Link {
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TextContent {
+    pub content: String,
+    pub link: Option<Link>,
+}
+
+/// A TeX expression, inline or as its own block.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Equation {
+    pub expression: String,
+}
+
+/// A page or database referenced by a mention.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Reference {
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LinkPreview {
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Mention {
+    User {
+        user: User,
+    },
+    Page {
+        page: Reference,
+    },
+    Database {
+        database: Reference,
+    },
+    Date {
+        date: Date,
+    },
+    LinkPreview {
+        link_preview: LinkPreview,
+    },
+    /// Mentions such as template placeholders, which are only shown through
+    /// their plain text.
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RichText {
+    Text {
+        text: TextContent,
+        annotations: Annotations,
+        plain_text: String,
+        href: Option<String>,
+    },
+    Mention {
+        mention: Mention,
+        annotations: Annotations,
+        plain_text: String,
+        href: Option<String>,
+    },
+    Equation {
+        equation: Equation,
+        annotations: Annotations,
+        plain_text: String,
+        href: Option<String>,
+    },
+}
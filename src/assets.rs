@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+
+use crate::Error;
+
+/// Decides where files hosted by Notion are linked to from the converted
+/// Markdown. Notion only hands out signed URLs that expire after an hour, so
+/// exports that outlive that need to copy the files somewhere stable.
+#[async_trait]
+pub trait AssetStore: Send + Sync {
+    /// Stores the file at the signed `url`, which belongs to the block with
+    /// the given ID, and returns the URL it should be linked to from now on.
+    async fn store(&self, url: &str, block_id: &str) -> Result<String, Error>;
+}
+
+/// Keeps linking to the signed URLs Notion hands out, for conversions that are
+/// used right away.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SignedUrls;
+
+#[async_trait]
+impl AssetStore for SignedUrls {
+    async fn store(&self, url: &str, _block_id: &str) -> Result<String, Error> {
+        Ok(url.to_string())
+    }
+}
+
+/// Downloads files into a local directory, named after the block they belong
+/// to, and links to them under `url_prefix`.
+#[derive(Debug, Clone)]
+pub struct LocalDirectory {
+    pub directory: PathBuf,
+    pub url_prefix: String,
+}
+
+impl LocalDirectory {
+    pub fn new(directory: impl Into<PathBuf>, url_prefix: impl Into<String>) -> Self {
+        LocalDirectory {
+            directory: directory.into(),
+            url_prefix: url_prefix.into(),
+        }
+    }
+}
+
+/// The extension of the file a URL points at, including the leading dot.
+fn extension(url: &str) -> &str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let name = path.rsplit('/').next().unwrap_or(path);
+
+    name.rfind('.').map_or("", |index| &name[index..])
+}
+
+#[async_trait]
+impl AssetStore for LocalDirectory {
+    async fn store(&self, url: &str, block_id: &str) -> Result<String, Error> {
+        let asset_error = |source: Box<dyn std::error::Error + Send + Sync>| Error::Asset {
+            block_id: block_id.to_string(),
+            source,
+        };
+
+        let bytes = reqwest::get(url)
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|error| asset_error(error.into()))?
+            .bytes()
+            .await
+            .map_err(|error| asset_error(error.into()))?;
+
+        let file_name = format!("{block_id}{}", extension(url));
+
+        std::fs::create_dir_all(&self.directory).map_err(|error| asset_error(error.into()))?;
+        std::fs::write(self.directory.join(&file_name), &bytes)
+            .map_err(|error| asset_error(error.into()))?;
+
+        Ok(format!(
+            "{}/{file_name}",
+            self.url_prefix.trim_end_matches('/')
+        ))
+    }
+}
@@ -0,0 +1,32 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Children of blocks by the ID of their parent, for content that's fetched
+/// ahead of being converted: the content of synced blocks, which an original
+/// and its copies share and so is only fetched once, and the pages of an
+/// export. Options cloned from one another share the same cache, which makes
+/// it last for a whole export run.
+///
+/// The blocks are cached rather than their converted content, as the same
+/// content converts differently depending on the page it ends up on.
+#[derive(Debug, Default)]
+pub struct BlockCache {
+    children: Mutex<HashMap<String, Arc<Vec<notion::Block>>>>,
+}
+
+impl BlockCache {
+    pub fn new() -> Self {
+        BlockCache::default()
+    }
+
+    pub(crate) fn get(&self, block_id: &str) -> Option<Arc<Vec<notion::Block>>> {
+        self.children.lock().unwrap().get(block_id).cloned()
+    }
+
+    pub(crate) fn insert(&self, block_id: &str, children: Arc<Vec<notion::Block>>) {
+        self.children
+            .lock()
+            .unwrap()
+            .insert(block_id.to_string(), children);
+    }
+}
@@ -0,0 +1,171 @@
+use crate::escape::{self, Context};
+use crate::options::Options;
+use crate::page_title;
+use crate::rich_text::{convert_rich_texts_in, link_destination};
+use crate::table::pipe_table;
+
+/// Whether [`property_text`] can render a kind of property. Others, such as
+/// relations and rollups, are left out of tables rather than shown as empty
+/// columns.
+fn is_supported(value: &notion::PropertyValue) -> bool {
+    matches!(
+        value,
+        notion::PropertyValue::Title { .. }
+            | notion::PropertyValue::RichText { .. }
+            | notion::PropertyValue::Number { .. }
+            | notion::PropertyValue::Select { .. }
+            | notion::PropertyValue::Status { .. }
+            | notion::PropertyValue::MultiSelect { .. }
+            | notion::PropertyValue::Date { .. }
+            | notion::PropertyValue::Formula { .. }
+            | notion::PropertyValue::People { .. }
+            | notion::PropertyValue::Checkbox { .. }
+            | notion::PropertyValue::Url { .. }
+            | notion::PropertyValue::Email { .. }
+            | notion::PropertyValue::PhoneNumber { .. }
+            | notion::PropertyValue::CreatedTime { .. }
+            | notion::PropertyValue::CreatedBy { .. }
+            | notion::PropertyValue::LastEditedTime { .. }
+            | notion::PropertyValue::LastEditedBy { .. }
+    )
+}
+
+/// Renders the value of a database property as the contents of a table cell.
+fn property_text(value: &notion::PropertyValue, options: &Options) -> String {
+    let text = |text: &str| escape::escape(text, Context::TableCell, false);
+    let date = |date: &notion::Date| match &date.end {
+        Some(end) => text(&format!("{} → {end}", date.start)),
+        None => text(&date.start.to_string()),
+    };
+    let user = |user: &notion::User| text(user.name.as_deref().unwrap_or_default());
+
+    match value {
+        notion::PropertyValue::Title { title, .. } => {
+            convert_rich_texts_in(title, options, Context::TableCell)
+        }
+        notion::PropertyValue::RichText { rich_text, .. } => {
+            convert_rich_texts_in(rich_text, options, Context::TableCell)
+        }
+        notion::PropertyValue::Number { number, .. } => {
+            number.map(|number| number.to_string()).unwrap_or_default()
+        }
+        notion::PropertyValue::Select { select, .. } => select
+            .as_ref()
+            .map(|option| text(&option.name))
+            .unwrap_or_default(),
+        notion::PropertyValue::Status { status, .. } => status
+            .as_ref()
+            .map(|option| text(&option.name))
+            .unwrap_or_default(),
+        notion::PropertyValue::MultiSelect { multi_select, .. } => multi_select
+            .iter()
+            .map(|option| text(&option.name))
+            .collect::<Vec<String>>()
+            .join(", "),
+        notion::PropertyValue::Date { date: value, .. } => {
+            value.as_ref().map(date).unwrap_or_default()
+        }
+        notion::PropertyValue::Formula { formula, .. } => match formula {
+            notion::FormulaResultValue::String { string } => {
+                string.as_deref().map(text).unwrap_or_default()
+            }
+            notion::FormulaResultValue::Number { number } => {
+                number.map(|number| number.to_string()).unwrap_or_default()
+            }
+            notion::FormulaResultValue::Boolean { boolean } => {
+                String::from(if *boolean == Some(true) { "✓" } else { "" })
+            }
+            notion::FormulaResultValue::Date { date: value } => {
+                value.as_ref().map(date).unwrap_or_default()
+            }
+        },
+        notion::PropertyValue::People { people, .. } => people
+            .iter()
+            .map(user)
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<String>>()
+            .join(", "),
+        notion::PropertyValue::Checkbox { checkbox, .. } => {
+            String::from(if *checkbox { "✓" } else { "" })
+        }
+        notion::PropertyValue::Url { url, .. } => url.as_deref().map(text).unwrap_or_default(),
+        notion::PropertyValue::Email { email, .. } => {
+            email.as_deref().map(text).unwrap_or_default()
+        }
+        notion::PropertyValue::PhoneNumber { phone_number, .. } => {
+            phone_number.as_deref().map(text).unwrap_or_default()
+        }
+        notion::PropertyValue::CreatedTime { created_time, .. } => text(&created_time.to_string()),
+        notion::PropertyValue::CreatedBy { created_by, .. } => user(created_by),
+        notion::PropertyValue::LastEditedTime {
+            last_edited_time, ..
+        } => text(&last_edited_time.to_string()),
+        notion::PropertyValue::LastEditedBy { last_edited_by, .. } => user(last_edited_by),
+        _ => String::new(),
+    }
+}
+
+/// Renders the pages of a database as a table with a column per property,
+/// starting with the title which links to the page itself.
+pub(crate) fn database_table(rows: &[notion::Page], options: &Options) -> String {
+    if rows.is_empty() {
+        return String::new();
+    }
+
+    let is_title =
+        |value: &notion::PropertyValue| matches!(value, notion::PropertyValue::Title { .. });
+
+    // Properties are unordered, so the title goes first and the rest follow
+    // alphabetically to keep exports stable
+    let mut columns = rows
+        .iter()
+        .flat_map(|row| row.properties.iter())
+        .filter(|(_, value)| !is_title(value) && is_supported(value))
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<&str>>();
+    columns.sort_unstable();
+    columns.dedup();
+
+    let title_column = rows
+        .iter()
+        .flat_map(|row| row.properties.iter())
+        .find(|(_, value)| is_title(value))
+        .map_or("Name", |(name, _)| name.as_str());
+
+    let mut table = vec![std::iter::once(title_column)
+        .chain(columns.iter().copied())
+        .map(|name| escape::escape(name, Context::TableCell, false))
+        .collect::<Vec<String>>()];
+
+    for row in rows.iter() {
+        let title = escape::escape(&page_title(&row.properties), Context::TableCell, false);
+        let url = options.link_resolver.page_url(&row.id);
+
+        let mut cells = vec![format!("[{title}]({})", link_destination(&url))];
+        cells.extend(columns.iter().map(|column| {
+            row.properties
+                .get(*column)
+                .map(|value| property_text(value, options))
+                .unwrap_or_default()
+        }));
+
+        table.push(cells);
+    }
+
+    pipe_table(&table, true)
+}
+
+/// Renders the pages of a database as a list of links. The links are resolved
+/// as child pages, so exporting a page tree exports every row as well.
+pub(crate) fn database_index(rows: &[notion::Page], options: &Options) -> String {
+    rows.iter()
+        .map(|row| {
+            let title = page_title(&row.properties);
+            let url = options.link_resolver.child_page_url(&row.id, &title);
+            let title = escape::escape(&title, Context::LinkText, false);
+
+            format!("* [{title}]({})", link_destination(&url))
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
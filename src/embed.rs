@@ -0,0 +1,184 @@
+use crate::escape;
+
+/// Percent-encodes a URL so it can be passed as a query parameter.
+fn encode_query(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// The host and path of a URL, without its scheme, `www.` prefix, query or
+/// fragment.
+fn host_and_path(url: &str) -> Option<(&str, &str)> {
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let url = url.split(['?', '#']).next()?;
+    let (host, path) = url.split_once('/').unwrap_or((url, ""));
+
+    Some((host.strip_prefix("www.").unwrap_or(host), path))
+}
+
+/// The value of a query parameter in a URL.
+fn query_parameter<'a>(url: &'a str, name: &str) -> Option<&'a str> {
+    let query = url.split_once('?')?.1.split('#').next()?;
+
+    query
+        .split('&')
+        .find_map(|pair| match pair.split_once('=') {
+            Some((key, value)) if key == name => Some(value),
+            _ => None,
+        })
+}
+
+/// The video ID of a `youtube.com/watch?v=`, `youtube.com/embed/` or
+/// `youtu.be/` URL.
+fn youtube_id(url: &str) -> Option<&str> {
+    let (host, path) = host_and_path(url)?;
+
+    let id = match host {
+        "youtu.be" => path,
+        "youtube.com" | "m.youtube.com" => match path {
+            "watch" => query_parameter(url, "v")?,
+            _ => path
+                .strip_prefix("embed/")
+                .or_else(|| path.strip_prefix("shorts/"))?,
+        },
+        _ => return None,
+    };
+
+    // IDs are always eleven URL-safe characters, which also keeps them safe
+    // to splice into other URLs
+    Some(id.trim_end_matches('/')).filter(|id| {
+        id.len() == 11
+            && id
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-')
+    })
+}
+
+/// The video ID of a `vimeo.com/` URL.
+fn vimeo_id(url: &str) -> Option<&str> {
+    match host_and_path(url)? {
+        ("vimeo.com", path) | ("player.vimeo.com", path) => path
+            .trim_start_matches("video/")
+            .split('/')
+            .next()
+            .filter(|id| !id.is_empty() && id.bytes().all(|byte| byte.is_ascii_digit())),
+        _ => None,
+    }
+}
+
+fn iframe(src: &str) -> String {
+    let src = escape::escape_html(src);
+
+    format!(
+        r#"<iframe src="{src}" width="100%" height="400" frameborder="0" allowfullscreen></iframe>"#
+    )
+}
+
+/// The HTML to embed a video from YouTube or Vimeo.
+pub(crate) fn video_html(url: &str) -> Option<String> {
+    if let Some(id) = youtube_id(url) {
+        return Some(iframe(&format!("https://www.youtube.com/embed/{id}")));
+    }
+
+    vimeo_id(url).map(|id| iframe(&format!("https://player.vimeo.com/video/{id}")))
+}
+
+/// The preview image of a YouTube video.
+pub(crate) fn youtube_thumbnail(url: &str) -> Option<String> {
+    youtube_id(url).map(|id| format!("https://img.youtube.com/vi/{id}/hqdefault.jpg"))
+}
+
+/// Whether a URL points at a video hosted by YouTube or Vimeo rather than a
+/// video file.
+pub(crate) fn is_video_provider(url: &str) -> bool {
+    youtube_id(url).is_some() || vimeo_id(url).is_some()
+}
+
+/// The HTML to embed a URL from a known provider, or `None` when the provider
+/// isn't recognised.
+pub(crate) fn embed_html(url: &str) -> Option<String> {
+    if let Some(html) = video_html(url) {
+        return Some(html);
+    }
+
+    let (host, path) = host_and_path(url)?;
+
+    match host {
+        "figma.com" => Some(iframe(&format!(
+            "https://www.figma.com/embed?embed_host=share&url={}",
+            encode_query(url)
+        ))),
+        "codepen.io" => {
+            let (user, id) = path.split_once("/pen/")?;
+
+            Some(iframe(&format!("https://codepen.io/{user}/embed/{id}")))
+        }
+        "gist.github.com" => {
+            let path = path.trim_end_matches('/');
+
+            Some(format!(
+                r#"<script src="https://gist.github.com/{}.js"></script>"#,
+                escape::escape_html(path)
+            ))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn youtube_ids() {
+        let id = Some("dQw4w9WgXcQ");
+
+        assert_eq!(
+            youtube_id("https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            id
+        );
+        assert_eq!(
+            youtube_id("https://youtube.com/watch?v=dQw4w9WgXcQ&t=42s"),
+            id
+        );
+        assert_eq!(
+            youtube_id("https://www.youtube.com/watch?t=42&v=dQw4w9WgXcQ"),
+            id
+        );
+        assert_eq!(youtube_id("https://youtu.be/dQw4w9WgXcQ?t=42"), id);
+        assert_eq!(youtube_id("https://www.youtube.com/embed/dQw4w9WgXcQ"), id);
+        assert_eq!(youtube_id("https://www.youtube.com/shorts/dQw4w9WgXcQ"), id);
+        assert_eq!(youtube_id("https://m.youtube.com/shorts/dQw4w9WgXcQ/"), id);
+    }
+
+    #[test]
+    fn youtube_ids_need_a_video() {
+        assert_eq!(youtube_id("https://www.youtube.com/watch"), None);
+        assert_eq!(youtube_id("https://www.youtube.com/@channel"), None);
+        assert_eq!(youtube_id("https://youtu.be/"), None);
+        assert_eq!(youtube_id("https://example.com/watch?v=dQw4w9WgXcQ"), None);
+        assert_eq!(youtube_id("https://youtu.be/a/b"), None);
+        assert_eq!(youtube_id("https://youtu.be/dQw4w9WgXc)"), None);
+        assert_eq!(
+            youtube_id("https://www.youtube.com/watch?v=dQw4w9WgXcQQ"),
+            None
+        );
+    }
+
+    #[test]
+    fn vimeo_ids() {
+        assert_eq!(vimeo_id("https://vimeo.com/76979871"), Some("76979871"));
+        assert_eq!(
+            vimeo_id("https://player.vimeo.com/video/76979871?h=abc"),
+            Some("76979871")
+        );
+        assert_eq!(vimeo_id("https://vimeo.com/channels/staffpicks"), None);
+    }
+}
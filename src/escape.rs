@@ -0,0 +1,182 @@
+/// Where a piece of text ends up in the document, which decides which
+/// characters would otherwise be read as Markdown syntax.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Context {
+    /// Regular inline text in a paragraph, heading, list item and so on.
+    Text,
+    /// The text of a `[text](url)` link.
+    LinkText,
+    /// The contents of a GFM table cell, which can't span multiple lines.
+    TableCell,
+}
+
+/// Backslash-escapes characters that would be read as inline formatting, and
+/// line-leading characters that would start a block such as a heading or list.
+pub(crate) fn escape(text: &str, context: Context, at_line_start: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut at_line_start = at_line_start && context == Context::Text;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            if context == Context::TableCell {
+                escaped.push_str("<br>");
+            } else {
+                escaped.push('\n');
+                at_line_start = context == Context::Text;
+            }
+            continue;
+        }
+
+        if at_line_start {
+            match c {
+                ' ' | '\t' => {
+                    escaped.push(c);
+                    continue;
+                }
+                '#' | '>' | '-' | '+' | '=' => escaped.push('\\'),
+                '0'..='9' => {
+                    // An ordered list marker is a run of digits followed by `.` or `)`
+                    escaped.push(c);
+                    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                        escaped.push(digit);
+                    }
+                    if let Some(delimiter) = chars.next_if(|&c| c == '.' || c == ')') {
+                        escaped.push('\\');
+                        escaped.push(delimiter);
+                    }
+                    at_line_start = false;
+                    continue;
+                }
+                _ => {}
+            }
+
+            at_line_start = false;
+        }
+
+        match c {
+            // Dollar signs would otherwise open inline math
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '~' | '|' | '$' => escaped.push('\\'),
+            // Only ampersands that could start an entity like `&amp;` or `&#38;`
+            '&' if chars
+                .peek()
+                .is_some_and(|&next| next == '#' || next.is_ascii_alphanumeric()) =>
+            {
+                escaped.push('\\')
+            }
+            _ => {}
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Backslash-escapes every ASCII punctuation character, which CommonMark turns
+/// back into the character itself, for text that has to come out of Markdown
+/// exactly as written.
+pub(crate) fn escape_punctuation(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if c.is_ascii_punctuation() {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// The longest run of consecutive backticks in `text`.
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+/// Wraps text in a code span, using a fence longer than any run of backticks
+/// inside it so the contents are kept verbatim.
+pub(crate) fn code_span(text: &str, context: Context) -> String {
+    let fence = "`".repeat(longest_backtick_run(text) + 1);
+    // A code span can't start or end with a backtick without padding
+    let padding = if text.starts_with('`') || text.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+
+    let text = match context {
+        // GFM splits table cells on pipes even within code spans
+        Context::TableCell => text.replace('|', "\\|").replace('\n', " "),
+        Context::Text | Context::LinkText => text.to_string(),
+    };
+
+    format!("{fence}{padding}{text}{padding}{fence}")
+}
+
+/// Wraps text in a fenced code block, using a fence longer than any run of
+/// backticks inside it so the contents are kept verbatim.
+pub(crate) fn code_block(text: &str, language: &str) -> String {
+    let fence = "`".repeat((longest_backtick_run(text) + 1).max(3));
+
+    format!("{fence}{language}\n{text}\n{fence}")
+}
+
+/// Escapes text for use inside HTML elements and attribute values.
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_block_markers_at_line_start() {
+        assert_eq!(escape("# Title", Context::Text, true), "\\# Title");
+        assert_eq!(escape("1. First", Context::Text, true), "1\\. First");
+        assert_eq!(escape("12) Twelfth", Context::Text, true), "12\\) Twelfth");
+        assert_eq!(escape("- Item", Context::Text, true), "\\- Item");
+        assert_eq!(escape("a\n  - b", Context::Text, false), "a\n  \\- b");
+    }
+
+    #[test]
+    fn leaves_block_markers_mid_line() {
+        assert_eq!(escape("# Title", Context::Text, false), "# Title");
+        assert_eq!(escape("Step 1. Go", Context::Text, true), "Step 1. Go");
+        assert_eq!(escape("- Item", Context::TableCell, true), "- Item");
+    }
+
+    #[test]
+    fn escapes_ampersands_that_start_entities() {
+        assert_eq!(escape("&amp;", Context::Text, false), "\\&amp;");
+        assert_eq!(escape("&#38;", Context::Text, false), "\\&#38;");
+        assert_eq!(escape("this & that", Context::Text, false), "this & that");
+    }
+
+    #[test]
+    fn code_span_fence_outgrows_backticks() {
+        assert_eq!(code_span("code", Context::Text), "`code`");
+        assert_eq!(code_span("a `b` c", Context::Text), "``a `b` c``");
+        assert_eq!(code_span("``x", Context::Text), "``` ``x ```");
+        assert_eq!(code_span("a|b", Context::TableCell), "`a\\|b`");
+    }
+
+    #[test]
+    fn code_block_fence_outgrows_backticks() {
+        assert_eq!(code_block("x", "rust"), "```rust\nx\n```");
+        assert_eq!(code_block("````", ""), "`````\n````\n`````");
+    }
+}
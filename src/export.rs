@@ -0,0 +1,450 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use notion::BlockType;
+
+use crate::cache::BlockCache;
+use crate::escape;
+use crate::links::LinkResolver;
+use crate::options::{ChildDatabases, ChildPages, Options};
+use crate::{
+    children_id, convert_blocks, fetch_tree, is_unqueryable, page_title, query_database,
+    retrieve_page, Error,
+};
+
+/// Decides where each page of a tree is written to when exporting it.
+pub trait PagePaths: Send + Sync {
+    /// The path of the file a page is exported to, relative to the export
+    /// directory. `parent` is the path of the page it's nested under, or
+    /// `None` for the page the export started from.
+    fn page_path(&self, parent: Option<&Path>, page_id: &str, title: &str) -> PathBuf;
+}
+
+/// Names files after a slug of the page title, nesting child pages in a
+/// directory named after their parent: `guide.md`, `guide/setup.md`, etc.
+/// Sibling pages with the same slug are told apart by [`export_page_tree`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Slugs;
+
+/// Lowercases a title and replaces anything but letters and digits with
+/// single dashes.
+fn slugify(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+impl PagePaths for Slugs {
+    fn page_path(&self, parent: Option<&Path>, page_id: &str, title: &str) -> PathBuf {
+        let slug = match slugify(title) {
+            slug if slug.is_empty() => page_id.replace('-', ""),
+            slug => slug,
+        };
+
+        match parent {
+            Some(parent) => parent.with_extension("").join(format!("{slug}.md")),
+            None => PathBuf::from(format!("{slug}.md")),
+        }
+    }
+}
+
+/// The path to `to` relative to the directory holding `from`, written with
+/// forward slashes so it can be used as a link.
+fn relative_link(from: &Path, to: &Path) -> String {
+    let from = from
+        .parent()
+        .map(|parent| parent.components().collect::<Vec<Component>>())
+        .unwrap_or_default();
+    let to = to.components().collect::<Vec<Component>>();
+
+    let common = from
+        .iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut segments = vec!["..".to_string(); from.len() - common];
+    segments.extend(
+        to[common..]
+            .iter()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned()),
+    );
+
+    segments.join("/")
+}
+
+/// Appends `-{suffix}` to the file name of `path`, before its extension.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+    path.with_file_name(match path.extension() {
+        Some(extension) => format!("{stem}-{suffix}.{}", extension.to_string_lossy()),
+        None => format!("{stem}-{suffix}"),
+    })
+}
+
+/// The files pages have been assigned to so far in an export, so that pages
+/// whose titles slug to the same name aren't written over each other.
+#[derive(Default)]
+struct Assigned {
+    /// Keyed by [`normalize_id`].
+    pages: HashMap<String, PathBuf>,
+    /// Lowercased, as some file systems don't tell `Guide.md` and `guide.md`
+    /// apart.
+    taken: HashSet<String>,
+}
+
+/// A page ID without dashes, as IDs given by users often leave them out.
+fn normalize_id(page_id: &str) -> String {
+    page_id.replace('-', "").to_lowercase()
+}
+
+impl Assigned {
+    fn get(&self, page_id: &str) -> Option<&PathBuf> {
+        self.pages.get(&normalize_id(page_id))
+    }
+
+    /// The path of a page, deciding it with `paths` the first time the page is
+    /// seen. A path that's already taken by another page gets the start of the
+    /// page ID appended, or all of it should that be taken too.
+    fn assign(
+        &mut self,
+        paths: &dyn PagePaths,
+        parent: Option<&Path>,
+        page_id: &str,
+        title: &str,
+    ) -> PathBuf {
+        let id = normalize_id(page_id);
+
+        if let Some(path) = self.pages.get(&id) {
+            return path.clone();
+        }
+
+        let key = |path: &Path| path.to_string_lossy().to_lowercase();
+
+        let path = paths.page_path(parent, page_id, title);
+        let short_id = id.chars().take(8).collect::<String>();
+        let path = [path.clone(), with_suffix(&path, &short_id)]
+            .into_iter()
+            .find(|path| !self.taken.contains(&key(path)))
+            .unwrap_or_else(|| with_suffix(&path, &id));
+
+        self.taken.insert(key(&path));
+        self.pages.insert(id, path.clone());
+
+        path
+    }
+}
+
+/// Links pages that are part of the export to the files they're exported to.
+struct ExportLinks {
+    inner: Arc<dyn LinkResolver>,
+    assigned: Arc<Assigned>,
+    path: PathBuf,
+}
+
+impl ExportLinks {
+    /// The relative link to a page that's been assigned a file in the export.
+    fn exported_url(&self, page_id: &str) -> Option<String> {
+        self.assigned
+            .get(page_id)
+            .map(|path| relative_link(&self.path, path))
+    }
+}
+
+impl LinkResolver for ExportLinks {
+    fn page_url(&self, page_id: &str) -> String {
+        self.exported_url(page_id)
+            .unwrap_or_else(|| self.inner.page_url(page_id))
+    }
+
+    fn database_url(&self, database_id: &str) -> String {
+        self.inner.database_url(database_id)
+    }
+
+    fn notion_link_url(&self, url: &str, page_id: &str) -> String {
+        self.exported_url(page_id)
+            .unwrap_or_else(|| self.inner.notion_link_url(url, page_id))
+    }
+
+    fn child_page_url(&self, page_id: &str, title: &str) -> String {
+        self.exported_url(page_id)
+            .unwrap_or_else(|| self.inner.child_page_url(page_id, title))
+    }
+}
+
+/// A page found while walking the tree, along with where it's exported to.
+struct ExportedPage {
+    id: String,
+    title: String,
+    path: PathBuf,
+}
+
+/// The child pages and databases nested in the already fetched blocks beneath
+/// `block_id`, in the order they appear in.
+fn nested_pages(cache: &BlockCache, block_id: &str, found: &mut Vec<notion::Block>) {
+    let Some(children) = cache.get(block_id) else {
+        return;
+    };
+
+    for child in children.iter() {
+        match &child.block {
+            BlockType::ChildPage { .. } | BlockType::ChildDatabase { .. } => {
+                found.push(child.clone())
+            }
+            _ if child.has_children => nested_pages(cache, children_id(child), found),
+            _ => {}
+        }
+    }
+}
+
+/// The pages linked to as children of a page, which are exported along with
+/// it: its child pages, and the rows of databases rendered as an index.
+async fn child_pages(
+    notion: &notion::Client,
+    page_id: &str,
+    options: &Options,
+) -> Result<Vec<(String, String)>, Error> {
+    let mut nested = vec![];
+    nested_pages(&options.block_cache, page_id, &mut nested);
+
+    let mut children = vec![];
+
+    for block in nested.iter() {
+        match &block.block {
+            BlockType::ChildPage { child_page } if options.child_pages == ChildPages::Link => {
+                children.push((block.id.clone(), child_page.title.clone()));
+            }
+            BlockType::ChildDatabase { .. } if options.child_databases == ChildDatabases::Index => {
+                // Databases that can't be queried are rendered as a link
+                let rows = match query_database(notion, &block.id).await {
+                    Ok(rows) => rows,
+                    Err(error) if is_unqueryable(&error) => continue,
+                    Err(error) => return Err(error),
+                };
+
+                children.extend(
+                    rows.iter()
+                        .map(|row| (row.id.clone(), page_title(&row.properties))),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    Ok(children)
+}
+
+/// Exports a page and every page nested under it into `directory`, with the
+/// location of each file decided by `paths`. The whole tree is walked before
+/// any page is converted, so links between any two pages in the export point
+/// at each other's files, and pages that would be written to the same file get
+/// a short ID appended to their file name. Returns the paths of all files
+/// written, relative to `directory`.
+pub async fn export_page_tree(
+    notion: &notion::Client,
+    page_id: &str,
+    directory: &Path,
+    options: &Options,
+    paths: impl PagePaths + 'static,
+) -> Result<Vec<PathBuf>, Error> {
+    // The blocks of every page are fetched while walking the tree and then
+    // converted from the cache, which only lives as long as the export
+    let options = Options {
+        block_cache: Arc::new(BlockCache::new()),
+        ..options.clone()
+    };
+
+    let (title, _) = retrieve_page(notion, page_id).await?;
+    let mut assigned = Assigned::default();
+    let path = assigned.assign(&paths, None, page_id, &title);
+
+    let mut pages = vec![];
+    let mut stack = vec![ExportedPage {
+        id: page_id.to_string(),
+        title,
+        path,
+    }];
+
+    while let Some(page) = stack.pop() {
+        fetch_tree(notion, &page.id, &options.block_cache, 0).await?;
+
+        let mut children = vec![];
+        for (child_id, title) in child_pages(notion, &page.id, &options).await? {
+            // A child page inside a synced block shows up on every page it's
+            // synced to, but is only exported once
+            if assigned.get(&child_id).is_some() {
+                continue;
+            }
+
+            let path = assigned.assign(&paths, Some(&page.path), &child_id, &title);
+            children.push(ExportedPage {
+                id: child_id,
+                title,
+                path,
+            });
+        }
+
+        // Children are exported right after their parent, in page order
+        stack.extend(children.into_iter().rev());
+        pages.push(page);
+    }
+
+    let assigned = Arc::new(assigned);
+    let mut written = vec![];
+
+    for page in pages.iter() {
+        let page_options = Options {
+            link_resolver: Arc::new(ExportLinks {
+                inner: options.link_resolver.clone(),
+                assigned: assigned.clone(),
+                path: page.path.clone(),
+            }),
+            ..options.clone()
+        };
+
+        let blocks = fetch_tree(notion, &page.id, &options.block_cache, 0).await?;
+        let body = convert_blocks(notion, &blocks, &page_options).await?;
+        let heading = escape::escape(&page.title, escape::Context::Text, false);
+
+        let file = directory.join(&page.path);
+        let io_error = |source| Error::Io {
+            path: file.clone(),
+            source,
+        };
+
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        std::fs::write(&file, format!("# {heading}\n\n{body}\n")).map_err(io_error)?;
+        written.push(page.path.clone());
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugs() {
+        assert_eq!(slugify("Getting Started"), "getting-started");
+        assert_eq!(slugify("  Q&A: What's new?  "), "q-a-what-s-new");
+        assert_eq!(slugify("Über Café"), "über-café");
+        assert_eq!(slugify("🚀"), "");
+    }
+
+    #[test]
+    fn relative_links() {
+        let link = |from: &str, to: &str| relative_link(Path::new(from), Path::new(to));
+
+        assert_eq!(link("guide.md", "intro.md"), "intro.md");
+        assert_eq!(link("guide.md", "guide/setup.md"), "guide/setup.md");
+        assert_eq!(link("guide/setup.md", "guide.md"), "../guide.md");
+        assert_eq!(
+            link("guide/setup/linux.md", "guide/usage/cli.md"),
+            "../usage/cli.md"
+        );
+        assert_eq!(link("a/b/c.md", "d.md"), "../../d.md");
+    }
+
+    #[test]
+    fn nested_page_paths() {
+        let parent = Slugs.page_path(None, "1", "Guide");
+        let child = Slugs.page_path(Some(&parent), "2", "Set up");
+
+        assert_eq!(parent, Path::new("guide.md"));
+        assert_eq!(child, Path::new("guide/set-up.md"));
+        assert_eq!(
+            Slugs.page_path(Some(&child), "3", "Linux"),
+            Path::new("guide/set-up/linux.md")
+        );
+        assert_eq!(
+            Slugs.page_path(None, "1429989f-e8ac-4eff-bc8f-57f56486db54", "🚀"),
+            Path::new("1429989fe8ac4effbc8f57f56486db54.md")
+        );
+    }
+
+    #[test]
+    fn colliding_paths_get_the_page_id() {
+        let mut assigned = Assigned::default();
+        let mut assign = |page_id, title| assigned.assign(&Slugs, None, page_id, title);
+
+        assert_eq!(assign("1429989f-e8ac", "Notes"), Path::new("notes.md"));
+        assert_eq!(
+            assign("2a7f0c31-9d1e", "notes"),
+            Path::new("notes-2a7f0c31.md")
+        );
+        assert_eq!(
+            assign("2a7f0c31-0000", "NOTES"),
+            Path::new("notes-2a7f0c310000.md")
+        );
+        assert_eq!(assign("1429989fe8ac", "Renamed"), Path::new("notes.md"));
+    }
+
+    fn block(id: &str, has_children: bool, block: BlockType) -> notion::Block {
+        notion::Block {
+            id: id.to_string(),
+            has_children,
+            block,
+        }
+    }
+
+    fn child_page(id: &str, title: &str) -> notion::Block {
+        block(
+            id,
+            true,
+            BlockType::ChildPage {
+                child_page: notion::ChildPage {
+                    title: title.to_string(),
+                },
+            },
+        )
+    }
+
+    #[test]
+    fn nested_pages_are_found_in_order() {
+        let cache = BlockCache::new();
+        let toggle = BlockType::Toggle {
+            toggle: notion::Text {
+                rich_text: vec![],
+                color: notion::Color::Default,
+            },
+        };
+        let copy = BlockType::SyncedBlock {
+            synced_block: notion::SyncedBlock {
+                synced_from: Some(notion::SyncedFrom {
+                    block_id: "original".to_string(),
+                }),
+            },
+        };
+
+        cache.insert(
+            "page",
+            Arc::new(vec![
+                child_page("a", "A"),
+                block("toggle", true, toggle),
+                block("copy", true, copy),
+                block("divider", false, BlockType::Divider),
+            ]),
+        );
+        cache.insert("toggle", Arc::new(vec![child_page("b", "B")]));
+        cache.insert("original", Arc::new(vec![child_page("c", "C")]));
+        // The content of child pages belongs to them, not the page
+        cache.insert("a", Arc::new(vec![child_page("d", "D")]));
+
+        let mut found = vec![];
+        nested_pages(&cache, "page", &mut found);
+
+        let ids = found
+            .iter()
+            .map(|block| block.id.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(ids, ["a", "b", "c"]);
+    }
+}
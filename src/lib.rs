@@ -1,112 +1,684 @@
 use async_recursion::async_recursion;
 
-#[derive(Debug)]
-pub enum Error {}
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
 
-use notion;
 use notion::BlockType;
 
-pub fn convert_rich_text(text: &notion::RichText) -> String {
-    match text {
-        notion::RichText::Text {
-            text, annotations, ..
-        } => {
-            let mut string = text.content.to_owned();
+mod assets;
+mod cache;
+mod database;
+mod embed;
+mod escape;
+mod export;
+mod links;
+mod options;
+mod rich_text;
+mod table;
+
+pub use assets::{AssetStore, LocalDirectory, SignedUrls};
+pub use cache::BlockCache;
+pub use export::{export_page_tree, PagePaths, Slugs};
+pub use links::{LinkResolver, NotionLinks};
+pub use options::{
+    BlockMath, ChildDatabases, ChildPages, Colors, Embeds, Images, InlineMath, Options, Pdfs,
+    Toggles, Underline, Videos,
+};
+pub use rich_text::{convert_rich_text, convert_rich_texts};
+pub use table::convert_table;
+
+use rich_text::plain_text;
+
+/// How deeply blocks may be nested before conversion gives up.
+pub const MAX_DEPTH: usize = 32;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A request to the Notion API failed while fetching the given block.
+    Api {
+        block_id: String,
+        source: notion::Error,
+    },
+    /// A value of the given block could not be serialized to its Notion name.
+    Serialization {
+        block_id: String,
+        source: serde_variant::UnsupportedType,
+    },
+    /// The given block is nested deeper than [`MAX_DEPTH`].
+    DepthLimit { block_id: String, depth: usize },
+    /// An exported page could not be written to the given path.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A file belonging to the given block could not be stored.
+    Asset {
+        block_id: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+impl Error {
+    /// The error code Notion answered a failed request with.
+    fn notion_code(&self) -> Option<notion::ErrorCode> {
+        match self {
+            Error::Api { source, .. } => source.code(),
+            _ => None,
+        }
+    }
+}
 
-            if annotations.bold {
-                string = format!("**{string}**");
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Api { block_id, source } => {
+                write!(
+                    f,
+                    "Notion API request for block {block_id} failed: {source}"
+                )
+            }
+            Error::Serialization { block_id, source } => {
+                write!(f, "could not serialize value of block {block_id}: {source}")
             }
+            Error::Io { path, source } => {
+                write!(f, "could not write {}: {source}", path.display())
+            }
+            Error::Asset { block_id, source } => {
+                write!(f, "could not store file of block {block_id}: {source}")
+            }
+            Error::DepthLimit { block_id, depth } => {
+                write!(
+                    f,
+                    "block {block_id} is nested {depth} levels deep, the limit is {MAX_DEPTH}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Api { source, .. } => Some(source),
+            Error::Serialization { source, .. } => Some(source),
+            Error::Io { source, .. } => Some(source),
+            Error::Asset { source, .. } => Some(source.as_ref()),
+            Error::DepthLimit { .. } => None,
+        }
+    }
+}
+
+/// A Notion page converted to Markdown.
+#[derive(Debug)]
+pub struct Page {
+    pub title: String,
+    pub properties: HashMap<String, notion::PropertyValue>,
+    pub body: String,
+}
+
+/// Children of a block after conversion, along with whether they start with a
+/// list item (which decides how tightly they attach to their parent).
+struct Children {
+    content: String,
+    starts_with_list: bool,
+}
+
+fn is_list_item(block: &BlockType) -> bool {
+    matches!(
+        block,
+        BlockType::BulletedListItem { .. }
+            | BlockType::NumberedListItem { .. }
+            | BlockType::ToDo { .. }
+    )
+}
+
+/// Bulleted items and to-dos share the `*` marker, so only numbered items start
+/// a separate list.
+fn is_numbered(block: &BlockType) -> bool {
+    matches!(block, BlockType::NumberedListItem { .. })
+}
+
+/// Fetches all children of a block, following `next_cursor` until Notion
+/// reports there are no more pages.
+pub(crate) async fn list_children(
+    notion: &notion::Client,
+    block_id: &str,
+) -> Result<Vec<notion::Block>, Error> {
+    let mut children = vec![];
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let page = notion
+            .blocks
+            .children()
+            .list(notion::BlockChildrenListOptions {
+                block_id,
+                start_cursor: cursor.as_deref(),
+            })
+            .await
+            .map_err(|source| Error::Api {
+                block_id: block_id.to_string(),
+                source,
+            })?;
+
+        children.extend(page.results);
+
+        match page.next_cursor {
+            Some(next_cursor) if page.has_more => cursor = Some(next_cursor),
+            _ => break,
+        }
+    }
+
+    Ok(children)
+}
+
+/// Fetches the children of a block, reusing them when they were already
+/// fetched ahead of conversion.
+async fn fetch_children(
+    notion: &notion::Client,
+    block_id: &str,
+    options: &Options,
+) -> Result<Arc<Vec<notion::Block>>, Error> {
+    match options.block_cache.get(block_id) {
+        Some(children) => Ok(children),
+        None => Ok(Arc::new(list_children(notion, block_id).await?)),
+    }
+}
+
+/// The ID of the block holding a block's children, which for copies of a
+/// synced block is the original.
+pub(crate) fn children_id(block: &notion::Block) -> &str {
+    match &block.block {
+        BlockType::SyncedBlock { synced_block } => synced_block
+            .synced_from
+            .as_ref()
+            .map_or(&block.id, |synced_from| &synced_from.block_id),
+        _ => &block.id,
+    }
+}
+
+/// Fetches the children of a block along with everything nested in them,
+/// caching every level so the content is only fetched once however many times
+/// it's converted. Child pages and databases are left out, as their content
+/// isn't converted along with the page holding them.
+#[async_recursion]
+pub(crate) async fn fetch_tree(
+    notion: &notion::Client,
+    block_id: &str,
+    cache: &BlockCache,
+    depth: usize,
+) -> Result<Arc<Vec<notion::Block>>, Error> {
+    if let Some(children) = cache.get(block_id) {
+        return Ok(children);
+    }
+
+    let children = Arc::new(list_children(notion, block_id).await?);
 
-            if annotations.italic {
-                string = format!("*{string}*");
+    // Anything deeper fails conversion with a depth error anyway
+    if depth < MAX_DEPTH {
+        for child in children.iter() {
+            let is_page = matches!(
+                child.block,
+                BlockType::ChildPage { .. } | BlockType::ChildDatabase { .. }
+            );
+
+            if child.has_children && !is_page {
+                fetch_tree(notion, children_id(child), cache, depth + 1).await?;
             }
+        }
+    }
+
+    cache.insert(block_id, children.clone());
+
+    Ok(children)
+}
+
+async fn convert_children(
+    notion: &notion::Client,
+    block: &notion::Block,
+    options: &Options,
+    depth: usize,
+) -> Result<Option<Children>, Error> {
+    if !block.has_children {
+        return Ok(None);
+    }
+
+    let children = fetch_children(notion, &block.id, options).await?;
+    let content = convert_nested_blocks(notion, &children, options, depth + 1).await?;
+
+    if content.is_empty() {
+        return Ok(None);
+    }
 
-            if annotations.code {
-                string = format!("`{string}`");
+    Ok(Some(Children {
+        starts_with_list: children
+            .first()
+            .is_some_and(|child| is_list_item(&child.block)),
+        content,
+    }))
+}
+
+/// Prefixes every non-empty line of `content` with `prefix`.
+fn indent(content: &str, prefix: &str) -> String {
+    content
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{prefix}{line}")
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Renders a list item, indenting continuation lines and children up to the
+/// item's content column.
+fn list_item(marker: &str, content: &str, children: Option<Children>) -> String {
+    let mut body = content.to_string();
+
+    if let Some(children) = children {
+        // Nested lists stay tight, anything else needs a blank line so it isn't
+        // read as a lazy continuation of the item's paragraph.
+        body.push_str(if children.starts_with_list {
+            "\n"
+        } else {
+            "\n\n"
+        });
+        body.push_str(&children.content);
+    }
+
+    let (first, rest) = body.split_once('\n').unwrap_or((body.as_str(), ""));
+    let rest = indent(rest, &" ".repeat(marker.len()));
+
+    if rest.is_empty() {
+        format!("{marker}{first}")
+    } else {
+        format!("{marker}{first}\n{rest}")
+    }
+}
+
+/// Renders a block quote, repeating the `>` marker on every line of its body.
+fn block_quote(content: &str, children: Option<Children>) -> String {
+    let mut string = content.to_string();
+
+    if let Some(children) = children {
+        string.push_str("\n\n");
+        string.push_str(&children.content);
+    }
+
+    string
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                ">".to_string()
+            } else {
+                format!("> {line}")
             }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
 
-            string
+/// Joins converted blocks with blank lines, except within runs of list items
+/// which are kept together as tight lists.
+fn join_blocks(output: &[(&notion::Block, String)]) -> String {
+    let mut string = String::new();
+    let mut previous: Option<&notion::Block> = None;
+
+    for (block, content) in output.iter() {
+        if let Some(previous) = previous {
+            let tight = is_list_item(&previous.block)
+                && is_list_item(&block.block)
+                && is_numbered(&previous.block) == is_numbered(&block.block);
+
+            string.push_str(if tight { "\n" } else { "\n\n" });
         }
-        _ => "".to_string(),
+
+        string.push_str(content);
+        previous = Some(block);
     }
+
+    string
+}
+
+/// The text of a page's title property.
+pub(crate) fn page_title(properties: &HashMap<String, notion::PropertyValue>) -> String {
+    properties
+        .values()
+        .find_map(|property| match property {
+            notion::PropertyValue::Title { title, .. } => {
+                Some(title.iter().map(plain_text).collect::<String>())
+            }
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Fetches all pages of a database, following `next_cursor` until Notion
+/// reports there are no more.
+pub(crate) async fn query_database(
+    notion: &notion::Client,
+    database_id: &str,
+) -> Result<Vec<notion::Page>, Error> {
+    let mut pages = vec![];
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let page = notion
+            .databases
+            .query(notion::DatabaseQueryOptions {
+                database_id,
+                start_cursor: cursor.as_deref(),
+            })
+            .await
+            .map_err(|source| Error::Api {
+                block_id: database_id.to_string(),
+                source,
+            })?;
+
+        pages.extend(page.results);
+
+        match page.next_cursor {
+            Some(next_cursor) if page.has_more => cursor = Some(next_cursor),
+            _ => break,
+        }
+    }
+
+    Ok(pages)
+}
+
+/// Whether a database query failed because the database can't be queried
+/// through the API, as with linked views of databases, which Notion answers
+/// with a not found or validation error. Other failures, such as rate limits,
+/// are passed on.
+pub(crate) fn is_unqueryable(error: &Error) -> bool {
+    matches!(
+        error.notion_code(),
+        Some(notion::ErrorCode::ObjectNotFound | notion::ErrorCode::ValidationError)
+    )
+}
+
+/// Fetches a page's title and properties.
+pub(crate) async fn retrieve_page(
+    notion: &notion::Client,
+    page_id: &str,
+) -> Result<(String, HashMap<String, notion::PropertyValue>), Error> {
+    let page = notion
+        .pages
+        .retrieve(notion::PageOptions { page_id })
+        .await
+        .map_err(|source| Error::Api {
+            block_id: page_id.to_string(),
+            source,
+        })?;
+
+    Ok((page_title(&page.properties), page.properties))
+}
+
+/// Fetches a page along with all of its blocks and converts it to Markdown.
+pub async fn convert_page(
+    notion: &notion::Client,
+    page_id: &str,
+    options: &Options,
+) -> Result<Page, Error> {
+    let (title, properties) = retrieve_page(notion, page_id).await?;
+
+    let blocks = list_children(notion, page_id).await?;
+    let body = convert_blocks(notion, &blocks, options).await?;
+
+    Ok(Page {
+        title,
+        properties,
+        body,
+    })
 }
 
-#[async_recursion]
 pub async fn convert_blocks(
     notion: &notion::Client,
-    blocks: &Vec<notion::Block>,
+    blocks: &[notion::Block],
+    options: &Options,
 ) -> Result<String, Error> {
-    let mut output = vec![];
+    convert_nested_blocks(notion, blocks, options, 0).await
+}
+
+/// Whether a URL can be written as a CommonMark autolink, which needs a scheme
+/// such as `https:` and can't hold spaces or angle brackets.
+fn is_autolink(url: &str) -> bool {
+    let has_scheme = url.split_once(':').is_some_and(|(scheme, _)| {
+        (2..=32).contains(&scheme.len())
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '.' | '-'))
+    });
+
+    has_scheme && !url.contains(|c: char| c.is_whitespace() || c == '<' || c == '>')
+}
+
+/// Renders a block that points at a URL as a link, using its caption as the
+/// link text when it has one.
+fn link_block(url: &str, caption: &[notion::RichText]) -> String {
+    let caption = caption.iter().map(plain_text).collect::<String>();
+    let caption = caption.trim();
+
+    // Relative links, such as those to exported pages, would be read as HTML
+    if caption.is_empty() && is_autolink(url) {
+        return format!("<{url}>");
+    }
+
+    let text = if caption.is_empty() { url } else { caption };
+
+    format!(
+        "[{}]({})",
+        escape::escape(text, escape::Context::LinkText, false),
+        rich_text::link_destination(url)
+    )
+}
+
+/// The URL a file should be linked to from the converted Markdown, passing
+/// files hosted by Notion through the asset store.
+async fn asset_url(
+    file: &notion::File,
+    block: &notion::Block,
+    options: &Options,
+) -> Result<String, Error> {
+    match file {
+        notion::File::External { external, .. } => Ok(external.url.to_owned()),
+        notion::File::File { file, .. } => options.asset_store.store(&file.url, &block.id).await,
+    }
+}
+
+/// Renders an image, using its caption as the alt text.
+fn image_markup(url: &str, caption: &str, options: &Options) -> String {
+    let src = escape::escape_html(url);
+    let alt = escape::escape_html(caption);
+
+    match options.images {
+        Images::Html => format!(r#"<img style="margin: 0 auto" src="{src}" alt="{alt}">"#),
+        Images::Figure => {
+            let figcaption = if caption.is_empty() {
+                String::new()
+            } else {
+                format!("\n<figcaption>{alt}</figcaption>")
+            };
+
+            format!("<figure>\n<img src=\"{src}\" alt=\"{alt}\">{figcaption}\n</figure>")
+        }
+        Images::Markdown => {
+            let alt = escape::escape(caption, escape::Context::LinkText, false);
+            let url = rich_text::link_destination(url);
+
+            if caption.is_empty() {
+                format!("![]({url})")
+            } else {
+                let title = caption.replace('\\', "\\\\").replace('"', "\\\"");
+
+                format!(r#"![{alt}]({url} "{title}")"#)
+            }
+        }
+    }
+}
+
+/// Renders a video, embedding YouTube and Vimeo URLs with their own player.
+fn video_markup(url: &str, caption: &[notion::RichText], options: &Options) -> String {
+    if !embed::is_video_provider(url) {
+        let src = escape::escape_html(url);
+
+        return format!(r#"<video controls src="{src}"></video>"#);
+    }
+
+    let html = match options.videos {
+        Videos::Embed => embed::video_html(url),
+        Videos::Thumbnail => None,
+    };
+
+    html.unwrap_or_else(|| match embed::youtube_thumbnail(url) {
+        Some(thumbnail) => {
+            let caption = caption.iter().map(plain_text).collect::<String>();
+            let alt = escape::escape(caption.trim(), escape::Context::LinkText, false);
+
+            format!(
+                "[![{alt}]({thumbnail})]({})",
+                rich_text::link_destination(url)
+            )
+        }
+        // Vimeo thumbnails can only be looked up through their API
+        None => link_block(url, caption),
+    })
+}
+
+fn file_caption(file: &notion::File) -> &[notion::RichText] {
+    match file {
+        notion::File::External { caption, .. } | notion::File::File { caption, .. } => caption,
+    }
+}
+
+/// Renders a file as a download link, named after its caption or otherwise the
+/// last segment of its URL.
+fn file_link(url: &str, caption: &[notion::RichText]) -> String {
+    let caption = caption.iter().map(plain_text).collect::<String>();
+    let name = match caption.trim() {
+        "" => url
+            .split(['?', '#'])
+            .next()
+            .and_then(|path| path.trim_end_matches('/').rsplit('/').next())
+            .unwrap_or(url),
+        caption => caption,
+    };
+
+    format!(
+        "[{}]({})",
+        escape::escape(name, escape::Context::LinkText, false),
+        rich_text::link_destination(url)
+    )
+}
+
+/// Renders a collapsible `<details>` section, with blank lines around the body
+/// so Markdown inside of it is still rendered.
+fn details(summary: &str, body: &str) -> String {
+    if body.is_empty() {
+        format!("<details>\n<summary>{summary}</summary>\n</details>")
+    } else {
+        format!("<details>\n<summary>{summary}</summary>\n\n{body}\n\n</details>")
+    }
+}
+
+#[async_recursion]
+async fn convert_nested_blocks(
+    notion: &notion::Client,
+    blocks: &[notion::Block],
+    options: &Options,
+    depth: usize,
+) -> Result<String, Error> {
+    if depth > MAX_DEPTH {
+        return Err(Error::DepthLimit {
+            block_id: blocks
+                .first()
+                .map(|block| block.id.clone())
+                .unwrap_or_default(),
+            depth,
+        });
+    }
+
+    let mut output: Vec<(&notion::Block, String)> = vec![];
+    // Position within the current run of numbered list items, 0 when outside one
+    let mut number = 0;
 
     for block in blocks.iter() {
         let string = match &block.block {
             BlockType::Heading1 { heading }
             | BlockType::Heading2 { heading }
             | BlockType::Heading3 { heading } => {
-                let content = heading
-                    .rich_text
-                    .iter()
-                    .map(|text| convert_rich_text(text))
-                    .collect::<String>();
+                let content = convert_rich_texts(&heading.rich_text, options);
 
                 let markdown_heading = match &block.block {
                     BlockType::Heading1 { .. } => "#",
                     BlockType::Heading2 { .. } => "##",
-                    BlockType::Heading3 { .. } | _ => "###",
+                    _ => "###",
                 };
 
-                Some(format!("{markdown_heading} {content}"))
+                let heading_content = format!("{markdown_heading} {content}");
+
+                // Toggleable headings hold the content collapsed beneath them
+                match convert_children(notion, block, options, depth).await? {
+                    Some(children) => Some(match options.toggle_headings {
+                        Toggles::Details => {
+                            let level = markdown_heading.len();
+                            let summary =
+                                rich_text::convert_rich_texts_html(&heading.rich_text, options);
+
+                            details(
+                                &format!("<h{level}>{summary}</h{level}>"),
+                                &children.content,
+                            )
+                        }
+                        Toggles::Flatten => format!("{heading_content}\n\n{}", children.content),
+                    }),
+                    None => Some(heading_content),
+                }
+            }
+            BlockType::Paragraph { paragraph, .. } => {
+                Some(convert_rich_texts(&paragraph.rich_text, options))
             }
-            BlockType::Paragraph { paragraph, .. } => Some(
-                paragraph
-                    .rich_text
-                    .iter()
-                    .map(|text| convert_rich_text(text))
-                    .collect::<String>(),
-            ),
             BlockType::Code { code, .. } => {
-                let language = serde_variant::to_variant_name(&code.language).unwrap();
-                let content = code
-                    .rich_text
-                    .iter()
-                    .map(|text| convert_rich_text(text))
-                    .collect::<String>();
+                let language =
+                    serde_variant::to_variant_name(&code.language).map_err(|source| {
+                        Error::Serialization {
+                            block_id: block.id.clone(),
+                            source,
+                        }
+                    })?;
+                // Code is kept verbatim, without annotations or escaping
+                let content = code.rich_text.iter().map(plain_text).collect::<String>();
 
-                Some(format!("```{language}\n{content}\n```"))
+                Some(escape::code_block(&content, language))
             }
             BlockType::BulletedListItem {
                 bulleted_list_item, ..
             } => {
-                let content = bulleted_list_item
-                    .rich_text
-                    .iter()
-                    .map(|text| convert_rich_text(text))
-                    .collect::<String>();
+                let content = convert_rich_texts(&bulleted_list_item.rich_text, options);
 
-                // TODO: Recurse down to `children`
+                let children = convert_children(notion, block, options, depth).await?;
 
-                Some(format!("* {content}"))
+                Some(list_item("* ", &content, children))
             }
             BlockType::NumberedListItem {
                 numbered_list_item, ..
             } => {
-                // TODO: Hold state for numbering
-                let content = numbered_list_item
-                    .rich_text
-                    .iter()
-                    .map(|text| convert_rich_text(text))
-                    .collect::<String>();
+                let content = convert_rich_texts(&numbered_list_item.rich_text, options);
 
-                // TODO: Recurse down to `children`
+                let children = convert_children(notion, block, options, depth).await?;
 
-                Some(format!("1. {content}"))
+                number += 1;
+
+                Some(list_item(&format!("{number}. "), &content, children))
             }
             BlockType::ToDo { to_do, .. } => {
-                let content = to_do
-                    .rich_text
-                    .iter()
-                    .map(|text| convert_rich_text(text))
-                    .collect::<String>();
+                let content = convert_rich_texts(&to_do.rich_text, options);
 
                 let checked = if to_do.checked.unwrap_or(false) {
                     "x"
@@ -114,61 +686,158 @@ pub async fn convert_blocks(
                     " "
                 };
 
-                // TODO: Recurse down to `children`
+                let children = convert_children(notion, block, options, depth).await?;
 
-                Some(format!("[{checked}] {content}"))
+                Some(list_item("* ", &format!("[{checked}] {content}"), children))
             }
             BlockType::Quote { quote, .. } => {
-                let content = quote
-                    .rich_text
-                    .iter()
-                    .map(|text| convert_rich_text(text))
-                    .collect::<String>();
+                let content = convert_rich_texts(&quote.rich_text, options);
 
-                // TODO: Recurse down to `children`
+                let children = convert_children(notion, block, options, depth).await?;
 
-                Some(format!("> {content}"))
+                Some(block_quote(&content, children))
             }
             BlockType::Callout { callout, .. } => {
-                let content = callout
-                    .rich_text
-                    .iter()
-                    .map(|text| convert_rich_text(text))
-                    .collect::<String>();
+                let content = convert_rich_texts(&callout.rich_text, options);
 
-                let icon = if let Some(value) = &callout.icon {
-                    match value {
-                        notion::Icon::Emoji { emoji, .. } => emoji,
-                        _ => "",
-                    }
-                } else {
-                    ""
+                let icon = match &callout.icon {
+                    Some(notion::Icon::Emoji { emoji, .. }) => emoji.as_str(),
+                    _ => "",
                 };
 
-                // TODO: Recurse down to `children`
+                let children = convert_children(notion, block, options, depth).await?;
 
-                Some(format!("> {icon} {content}"))
+                Some(block_quote(&format!("{icon} {content}"), children))
             }
             BlockType::Image { image, .. } => {
-                match &image {
-                    notion::File::External { external, .. } => {
-                        let url = &external.url;
-                        Some(format!(r#"<img style="margin: 0 auto" src="{url}">"#))
-                    }
-                    // TODO: Implement reupload of Notion file type
-                    _ => None,
-                }
+                let url = asset_url(image, block, options).await?;
+                let caption = file_caption(image)
+                    .iter()
+                    .map(plain_text)
+                    .collect::<String>();
+                let caption = caption.trim();
+
+                Some(image_markup(&url, caption, options))
             }
             BlockType::Video { video, .. } => {
-                match &video {
-                    notion::File::External { external, .. } => {
-                        let url = &external.url;
-                        Some(format!(r#"<video controls src="{url}" />"#))
+                let url = asset_url(video, block, options).await?;
+
+                Some(video_markup(&url, file_caption(video), options))
+            }
+            BlockType::File { file, .. } => {
+                let url = asset_url(file, block, options).await?;
+
+                Some(file_link(&url, file_caption(file)))
+            }
+            BlockType::Pdf { pdf, .. } => {
+                let url = asset_url(pdf, block, options).await?;
+                let link = file_link(&url, file_caption(pdf));
+
+                Some(match options.pdfs {
+                    Pdfs::Link => link,
+                    Pdfs::Object => {
+                        let data = escape::escape_html(&url);
+
+                        format!(
+                            r#"<object data="{data}" type="application/pdf" width="100%" height="600">{link}</object>"#
+                        )
+                    }
+                })
+            }
+            BlockType::Bookmark { bookmark, .. } => {
+                Some(link_block(&bookmark.url, &bookmark.caption))
+            }
+            BlockType::LinkPreview { link_preview, .. } => Some(link_block(&link_preview.url, &[])),
+            BlockType::Embed { embed, .. } => Some(
+                match options.embeds {
+                    Embeds::Html => embed::embed_html(&embed.url),
+                    Embeds::Link => None,
+                }
+                .unwrap_or_else(|| link_block(&embed.url, &embed.caption)),
+            ),
+            BlockType::ChildPage { child_page, .. } => match options.child_pages {
+                ChildPages::Link => {
+                    let url = options
+                        .link_resolver
+                        .child_page_url(&block.id, &child_page.title);
+                    let title = escape::escape(&child_page.title, escape::Context::LinkText, false);
+
+                    Some(format!("[{title}]({})", rich_text::link_destination(&url)))
+                }
+                ChildPages::Ignore => None,
+            },
+            BlockType::ChildDatabase { child_database, .. } => {
+                match query_database(notion, &block.id).await {
+                    Ok(rows) => {
+                        let title = match child_database.title.trim() {
+                            "" => String::new(),
+                            title => format!(
+                                "**{}**",
+                                escape::escape(title, escape::Context::Text, false)
+                            ),
+                        };
+
+                        let content = match options.child_databases {
+                            ChildDatabases::Table => database::database_table(&rows, options),
+                            ChildDatabases::Index => database::database_index(&rows, options),
+                        };
+
+                        let string = [title, content]
+                            .into_iter()
+                            .filter(|part| !part.is_empty())
+                            .collect::<Vec<String>>()
+                            .join("\n\n");
+
+                        Some(string).filter(|string| !string.is_empty())
+                    }
+                    // Linked views of databases are linked to instead
+                    Err(error) if is_unqueryable(&error) => {
+                        let url = options.link_resolver.database_url(&block.id);
+                        let title = match child_database.title.trim() {
+                            "" => "Untitled".to_string(),
+                            title => escape::escape(title, escape::Context::LinkText, false),
+                        };
+
+                        Some(format!("[{title}]({})", rich_text::link_destination(&url)))
                     }
-                    // TODO: Implement reupload of Notion file type
-                    _ => None,
+                    Err(error) => return Err(error),
                 }
             }
+            BlockType::LinkToPage { link_to_page, .. } => match link_to_page {
+                notion::LinkToPage::Page { page_id } => {
+                    // Pages that aren't shared with the integration can't be
+                    // retrieved, but can still be linked to
+                    let title = match retrieve_page(notion, page_id).await {
+                        Ok((title, _)) => title,
+                        Err(error)
+                            if error.notion_code() == Some(notion::ErrorCode::ObjectNotFound) =>
+                        {
+                            String::new()
+                        }
+                        Err(error) => return Err(error),
+                    };
+                    let url = options.link_resolver.page_url(page_id);
+                    let title = match title.trim() {
+                        "" => "Untitled".to_string(),
+                        title => escape::escape(title, escape::Context::LinkText, false),
+                    };
+
+                    Some(format!("[{title}]({})", rich_text::link_destination(&url)))
+                }
+                notion::LinkToPage::Database { database_id } => Some(link_block(
+                    &options.link_resolver.database_url(database_id),
+                    &[],
+                )),
+                notion::LinkToPage::Unsupported => None,
+            },
+            BlockType::Equation { equation, .. } => {
+                let expression = equation.expression.trim();
+
+                Some(match options.block_math {
+                    BlockMath::Dollars => format!("$$\n{expression}\n$$"),
+                    BlockMath::Fence => escape::code_block(expression, "math"),
+                })
+            }
             BlockType::Divider => Some("---".to_string()),
             BlockType::Unsupported => {
                 // println!("Did not catch {string}");
@@ -176,29 +845,15 @@ pub async fn convert_blocks(
             }
             BlockType::ColumnList { .. } => {
                 if block.has_children {
-                    let columns = notion
-                        .blocks
-                        .children()
-                        .list(notion::BlockChildrenListOptions {
-                            block_id: &block.id,
-                        })
-                        .await
-                        .unwrap()
-                        .results;
+                    let columns = fetch_children(notion, &block.id, options).await?;
 
                     let mut content = vec![];
                     for column in columns.iter() {
-                        let children = notion
-                            .blocks
-                            .children()
-                            .list(notion::BlockChildrenListOptions {
-                                block_id: &column.id,
-                            })
-                            .await
-                            .unwrap()
-                            .results;
-
-                        content.push(convert_blocks(&notion, &children).await.unwrap());
+                        let children = fetch_children(notion, &column.id, options).await?;
+
+                        content.push(
+                            convert_nested_blocks(notion, &children, options, depth + 1).await?,
+                        );
                     }
 
                     Some(format!(
@@ -214,29 +869,195 @@ pub async fn convert_blocks(
                 }
             }
 
+            // Copies point at the original block, which holds the content
+            BlockType::SyncedBlock { .. } => {
+                let children =
+                    fetch_tree(notion, children_id(block), &options.block_cache, depth + 1).await?;
+                let content = convert_nested_blocks(notion, &children, options, depth + 1).await?;
+
+                if content.is_empty() {
+                    None
+                } else {
+                    Some(content)
+                }
+            }
+
+            BlockType::Toggle { toggle, .. } => {
+                let children = convert_children(notion, block, options, depth).await?;
+
+                match options.toggles {
+                    Toggles::Details => {
+                        let summary =
+                            rich_text::convert_rich_texts_html(&toggle.rich_text, options);
+
+                        Some(details(
+                            &summary,
+                            children.as_ref().map_or("", |children| &children.content),
+                        ))
+                    }
+                    Toggles::Flatten => {
+                        let content = convert_rich_texts(&toggle.rich_text, options);
+
+                        Some(match children {
+                            Some(children) => format!("### {content}\n\n{}", children.content),
+                            None => format!("### {content}"),
+                        })
+                    }
+                }
+            }
+
+            BlockType::Table { table, .. } => {
+                let rows = fetch_children(notion, &block.id, options)
+                    .await?
+                    .iter()
+                    .filter_map(|row| match &row.block {
+                        BlockType::TableRow { table_row, .. } => Some(table_row.cells.clone()),
+                        _ => None,
+                    })
+                    .collect::<Vec<Vec<Vec<notion::RichText>>>>();
+
+                Some(convert_table(
+                    &rows,
+                    table.has_column_header,
+                    table.has_row_header,
+                    options,
+                ))
+                .filter(|string| !string.is_empty())
+            }
+            // Rows are converted along with the table they belong to
+            BlockType::TableRow { .. } => None,
+
             BlockType::Column { .. }
-            | BlockType::Table
-            | BlockType::Bookmark { .. }
-            | BlockType::File { .. }
-            | BlockType::Pdf { .. }
             | BlockType::TableOfContents
-            | BlockType::ChildPage { .. }
-            | BlockType::ChildDatabase { .. }
-            | BlockType::SyncedBlock
             | BlockType::Template
-            | BlockType::Toggle
-            | BlockType::Breadcrumb
-            | BlockType::Embed { .. }
-            | BlockType::Equation { .. }
-            | BlockType::LinkPreview { .. }
-            | BlockType::TableRow
-            | BlockType::LinkToPage { .. } => None,
+            | BlockType::Breadcrumb => None,
         };
 
         if let Some(string) = string {
-            output.push(string);
+            if !is_numbered(&block.block) {
+                number = 0;
+            }
+
+            output.push((block, string));
         }
     }
 
-    Ok(output.join("\n\n"))
+    Ok(join_blocks(&output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn children(content: &str, starts_with_list: bool) -> Option<Children> {
+        Some(Children {
+            content: content.to_string(),
+            starts_with_list,
+        })
+    }
+
+    #[test]
+    fn indent_skips_blank_lines() {
+        assert_eq!(indent("a\n\nb", "  "), "  a\n\n  b");
+        assert_eq!(indent("", "  "), "");
+    }
+
+    #[test]
+    fn list_items_indent_to_their_content() {
+        assert_eq!(list_item("* ", "a", None), "* a");
+        assert_eq!(list_item("10. ", "a\nb", None), "10. a\n    b");
+        // Nested lists attach tightly, other children follow a blank line
+        assert_eq!(
+            list_item("* ", "a", children("* b\n* c", true)),
+            "* a\n  * b\n  * c"
+        );
+        assert_eq!(
+            list_item("1. ", "a", children("b\n\nc", false)),
+            "1. a\n\n   b\n\n   c"
+        );
+    }
+
+    #[test]
+    fn block_quotes_mark_every_line() {
+        assert_eq!(block_quote("a\nb", None), "> a\n> b");
+        assert_eq!(
+            block_quote("a", children("* b\n\n  c", true)),
+            "> a\n>\n> * b\n>\n>   c"
+        );
+    }
+
+    fn text() -> notion::Text {
+        notion::Text {
+            rich_text: vec![],
+            color: Default::default(),
+        }
+    }
+
+    fn block(block: BlockType) -> notion::Block {
+        notion::Block {
+            id: String::new(),
+            has_children: false,
+            block,
+        }
+    }
+
+    #[test]
+    fn numbered_items_stay_tight() {
+        let paragraph = block(BlockType::Paragraph { paragraph: text() });
+        let numbered = block(BlockType::NumberedListItem {
+            numbered_list_item: text(),
+        });
+
+        assert_eq!(
+            join_blocks(&[
+                (&paragraph, "a".to_string()),
+                (&numbered, "1. b".to_string()),
+                (&numbered, "2. c".to_string()),
+                (&paragraph, "d".to_string()),
+            ]),
+            "a\n\n1. b\n2. c\n\nd"
+        );
+    }
+
+    #[test]
+    fn list_runs_split_on_numbering() {
+        let bulleted = block(BlockType::BulletedListItem {
+            bulleted_list_item: text(),
+        });
+        let to_do = block(BlockType::ToDo {
+            to_do: notion::ToDo {
+                rich_text: vec![],
+                checked: Some(true),
+            },
+        });
+        let numbered = block(BlockType::NumberedListItem {
+            numbered_list_item: text(),
+        });
+
+        // Bullets and to-dos share a marker, so only numbering starts a new list
+        assert_eq!(
+            join_blocks(&[
+                (&bulleted, "* a".to_string()),
+                (&to_do, "* [x] b".to_string()),
+                (&numbered, "1. c".to_string()),
+                (&bulleted, "* d".to_string()),
+            ]),
+            "* a\n* [x] b\n\n1. c\n\n* d"
+        );
+    }
+
+    #[test]
+    fn link_blocks() {
+        assert_eq!(
+            link_block("https://example.com/a", &[]),
+            "<https://example.com/a>"
+        );
+        assert_eq!(
+            link_block("mailto:a@example.com", &[]),
+            "<mailto:a@example.com>"
+        );
+        // Relative links aren't autolinks, and `<db.md>` would be read as HTML
+        assert_eq!(link_block("db.md", &[]), "[db.md](db.md)");
+        assert_eq!(link_block("../a b.md", &[]), "[../a b.md](<../a b.md>)");
+    }
 }
@@ -0,0 +1,144 @@
+/// Decides where references to other Notion pages point in the converted
+/// Markdown, so exports can link to each other instead of back to Notion.
+pub trait LinkResolver: Send + Sync {
+    /// The URL to link to for the page with the given ID.
+    fn page_url(&self, page_id: &str) -> String;
+
+    /// The URL to link to for the database with the given ID, which defaults to
+    /// resolving it like a page.
+    fn database_url(&self, database_id: &str) -> String {
+        self.page_url(database_id)
+    }
+
+    /// The URL to link to for a page nested under the page being converted,
+    /// which defaults to resolving it like any other page.
+    fn child_page_url(&self, page_id: &str, _title: &str) -> String {
+        self.page_url(page_id)
+    }
+
+    /// The URL to link to for a hyperlink in text pointing at a Notion page,
+    /// such as `https://www.notion.so/Title-<id>`, which defaults to resolving
+    /// the linked page.
+    fn notion_link_url(&self, _url: &str, page_id: &str) -> String {
+        self.page_url(page_id)
+    }
+}
+
+/// Keeps every reference pointing at the page on notion.so.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NotionLinks;
+
+impl LinkResolver for NotionLinks {
+    fn page_url(&self, page_id: &str) -> String {
+        format!("https://www.notion.so/{}", page_id.replace('-', ""))
+    }
+
+    fn notion_link_url(&self, url: &str, _page_id: &str) -> String {
+        // Links between pages in the same workspace are stored as `/<id>`
+        match url.strip_prefix('/') {
+            Some(path) => format!("https://www.notion.so/{path}"),
+            None => url.to_string(),
+        }
+    }
+}
+
+/// Formats 32 hex digits as a dashed UUID, the form the Notion API uses for
+/// IDs.
+fn dashed_id(hex: &str) -> String {
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// The ID of the page a URL points at, when it's a link to a Notion page like
+/// `https://www.notion.so/Title-<id>` or the `/<id>` form used for links within
+/// a workspace.
+pub(crate) fn notion_page_id(url: &str) -> Option<String> {
+    let path = match url.split_once("://") {
+        Some((_, rest)) => {
+            let (host, path) = rest.split_once('/')?;
+            let host = host.strip_prefix("www.").unwrap_or(host);
+
+            if host != "notion.so" && !host.ends_with(".notion.site") {
+                return None;
+            }
+
+            path
+        }
+        None => url.strip_prefix('/')?,
+    };
+
+    let path = path.split(['?', '#']).next()?;
+    let segment = path.trim_end_matches('/').rsplit('/').next()?;
+    let is_id = |hex: &str| hex.len() == 32 && hex.bytes().all(|byte| byte.is_ascii_hexdigit());
+
+    let hex = if segment.len() == 36 && is_id(&segment.replace('-', "")) {
+        segment.replace('-', "")
+    } else {
+        segment
+            .get(segment.len().checked_sub(32)?..)
+            .filter(|hex| is_id(hex))?
+            .to_string()
+    };
+
+    Some(dashed_id(&hex.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "1429989f-e8ac-4eff-bc8f-57f56486db54";
+
+    #[test]
+    fn page_ids_from_notion_urls() {
+        let id = Some(ID.to_string());
+
+        assert_eq!(
+            notion_page_id("https://www.notion.so/Title-1429989fe8ac4effbc8f57f56486db54"),
+            id
+        );
+        assert_eq!(
+            notion_page_id("https://notion.so/workspace/1429989FE8AC4EFFBC8F57F56486DB54?pvs=4"),
+            id
+        );
+        assert_eq!(
+            notion_page_id("https://www.notion.so/1429989f-e8ac-4eff-bc8f-57f56486db54"),
+            id
+        );
+        assert_eq!(notion_page_id("/1429989fe8ac4effbc8f57f56486db54"), id);
+    }
+
+    #[test]
+    fn page_ids_from_notion_sites() {
+        assert_eq!(
+            notion_page_id("https://acme.notion.site/Guide-1429989fe8ac4effbc8f57f56486db54"),
+            Some(ID.to_string())
+        );
+        assert_eq!(
+            notion_page_id("https://notion.site.example.com/1429989fe8ac4effbc8f57f56486db54"),
+            None
+        );
+    }
+
+    #[test]
+    fn page_ids_need_a_full_id() {
+        assert_eq!(
+            notion_page_id("https://www.notion.so/Title-1429989fe8ac4effbc8f57f56486db5"),
+            None
+        );
+        assert_eq!(
+            notion_page_id("https://www.notion.so/1429989f-e8ac-4eff-bc8f-57f56486db5x"),
+            None
+        );
+        assert_eq!(
+            notion_page_id("https://example.com/1429989fe8ac4effbc8f57f56486db54"),
+            None
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::assets::{AssetStore, SignedUrls};
+use crate::cache::BlockCache;
+use crate::links::{LinkResolver, NotionLinks};
+
+/// Settings that control how Notion content is rendered to Markdown.
+#[derive(Clone)]
+pub struct Options {
+    /// How underlined text is rendered, as Markdown has no syntax for it.
+    pub underline: Underline,
+    /// How text and background colors are rendered.
+    pub colors: Colors,
+    /// Which delimiters surround inline equations.
+    pub inline_math: InlineMath,
+    /// How equation blocks are rendered.
+    pub block_math: BlockMath,
+    /// How toggle blocks are rendered.
+    pub toggles: Toggles,
+    /// How the content beneath toggleable headings is rendered.
+    pub toggle_headings: Toggles,
+    /// How embed blocks are rendered.
+    pub embeds: Embeds,
+    /// How image blocks are rendered.
+    pub images: Images,
+    /// How videos hosted by YouTube or Vimeo are rendered.
+    pub videos: Videos,
+    /// How PDF blocks are rendered.
+    pub pdfs: Pdfs,
+    /// How blocks for pages nested under the converted page are rendered.
+    pub child_pages: ChildPages,
+    /// How databases inside the converted page are rendered.
+    pub child_databases: ChildDatabases,
+    /// Where references to other Notion pages link to.
+    pub link_resolver: Arc<dyn LinkResolver>,
+    /// Where files hosted by Notion are stored.
+    pub asset_store: Arc<dyn AssetStore>,
+    /// Blocks fetched ahead of conversion, such as the content of synced
+    /// blocks, shared by options cloned from one another.
+    pub block_cache: Arc<BlockCache>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            underline: Underline::default(),
+            colors: Colors::default(),
+            inline_math: InlineMath::default(),
+            block_math: BlockMath::default(),
+            toggles: Toggles::default(),
+            toggle_headings: Toggles::Flatten,
+            embeds: Embeds::default(),
+            images: Images::default(),
+            videos: Videos::default(),
+            pdfs: Pdfs::default(),
+            child_pages: ChildPages::default(),
+            child_databases: ChildDatabases::default(),
+            link_resolver: Arc::new(NotionLinks),
+            asset_store: Arc::new(SignedUrls),
+            block_cache: Arc::new(BlockCache::new()),
+        }
+    }
+}
+
+impl fmt::Debug for Options {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Options")
+            .field("underline", &self.underline)
+            .field("colors", &self.colors)
+            .field("inline_math", &self.inline_math)
+            .field("block_math", &self.block_math)
+            .field("toggles", &self.toggles)
+            .field("toggle_headings", &self.toggle_headings)
+            .field("embeds", &self.embeds)
+            .field("images", &self.images)
+            .field("videos", &self.videos)
+            .field("pdfs", &self.pdfs)
+            .field("child_pages", &self.child_pages)
+            .field("child_databases", &self.child_databases)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Underline {
+    /// Wrap underlined text in `<u>` tags.
+    #[default]
+    Html,
+    /// Render underlined text as plain text.
+    Ignore,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Colors {
+    /// Drop colors entirely.
+    #[default]
+    Ignore,
+    /// Wrap colored text in a `<span>` with an inline `color` or
+    /// `background-color` style.
+    Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InlineMath {
+    /// `$expression$`, as understood by GitHub and KaTeX.
+    #[default]
+    Dollars,
+    /// `\\(expression\\)` in the Markdown source, with the punctuation of the
+    /// expression backslash-escaped, which renders to the `\(expression\)`
+    /// MathJax expects by default.
+    Parentheses,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlockMath {
+    /// A `$$` delimited display block.
+    #[default]
+    Dollars,
+    /// A fenced code block with the `math` language, as rendered by GitHub.
+    Fence,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Toggles {
+    /// A collapsible `<details>` section with the toggle's text as its
+    /// summary and its children inside.
+    #[default]
+    Details,
+    /// The toggle's text as a heading followed by its children, for targets
+    /// that don't support HTML.
+    Flatten,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Embeds {
+    /// A link to the embedded URL.
+    #[default]
+    Link,
+    /// An `<iframe>` or script for known providers such as YouTube, Figma,
+    /// CodePen and GitHub Gists, falling back to a link for anything else.
+    Html,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Images {
+    /// A centered `<img>` tag with the caption as its alt text.
+    #[default]
+    Html,
+    /// A `<figure>` with the caption shown below the image as a `<figcaption>`.
+    Figure,
+    /// Plain Markdown `![alt](url "caption")` without any styling.
+    Markdown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Videos {
+    /// The provider's own player in an `<iframe>`.
+    #[default]
+    Embed,
+    /// A preview image linking to the video, for targets that strip iframes.
+    Thumbnail,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pdfs {
+    /// A download link, like any other file.
+    #[default]
+    Link,
+    /// An inline `<object>` viewer, with the download link as its fallback.
+    Object,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChildPages {
+    /// A link to the page, resolved through the link resolver.
+    #[default]
+    Link,
+    /// Leave child pages out of the converted Markdown.
+    Ignore,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChildDatabases {
+    /// A table with a row per page and a column per property.
+    #[default]
+    Table,
+    /// A list of links to each page, which are exported along with their
+    /// parent when exporting a page tree.
+    Index,
+}
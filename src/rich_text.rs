@@ -0,0 +1,516 @@
+use crate::escape::{self, Context};
+use crate::links;
+use crate::options::{Colors, InlineMath, Options, Underline};
+
+/// A stretch of text sharing the same annotations and link, which is wrapped
+/// in formatting markers as a whole.
+struct Run<'a> {
+    content: String,
+    annotations: &'a notion::Annotations,
+    url: Option<String>,
+    /// Whether the content is a TeX expression, which is kept verbatim.
+    math: bool,
+}
+
+/// The unformatted text of a rich text element, as Notion displays it.
+pub(crate) fn plain_text(text: &notion::RichText) -> &str {
+    match text {
+        notion::RichText::Text { plain_text, .. }
+        | notion::RichText::Mention { plain_text, .. }
+        | notion::RichText::Equation { plain_text, .. } => plain_text,
+    }
+}
+
+/// Formats a URL as a Markdown link destination, wrapping it in angle brackets
+/// when it contains characters that would otherwise end the destination early.
+pub(crate) fn link_destination(url: &str) -> String {
+    if url.contains(|c: char| c.is_whitespace() || c == '(' || c == ')') {
+        format!("<{url}>")
+    } else {
+        url.to_string()
+    }
+}
+
+/// The inline CSS for a Notion color, or `None` for the default color.
+fn color_style(color: &notion::Color) -> Option<String> {
+    match serde_variant::to_variant_name(color).ok()? {
+        "default" => None,
+        name => Some(match name.strip_suffix("_background") {
+            Some(name) => format!("background-color: {name}"),
+            None => format!("color: {name}"),
+        }),
+    }
+}
+
+fn same_annotations(a: &notion::Annotations, b: &notion::Annotations) -> bool {
+    a.bold == b.bold
+        && a.italic == b.italic
+        && a.strikethrough == b.strikethrough
+        && a.underline == b.underline
+        && a.code == b.code
+        && serde_variant::to_variant_name(&a.color).ok()
+            == serde_variant::to_variant_name(&b.color).ok()
+}
+
+/// Renders a date mention as its start, and end when it spans a range.
+fn format_date(date: &notion::Date) -> String {
+    match &date.end {
+        Some(end) => format!("{} → {end}", date.start),
+        None => date.start.to_string(),
+    }
+}
+
+fn to_run<'a>(text: &'a notion::RichText, options: &Options) -> Run<'a> {
+    match text {
+        notion::RichText::Text {
+            text, annotations, ..
+        } => Run {
+            content: text.content.to_owned(),
+            annotations,
+            url: text
+                .link
+                .as_ref()
+                .map(|link| match links::notion_page_id(&link.url) {
+                    Some(page_id) => options.link_resolver.notion_link_url(&link.url, &page_id),
+                    None => link.url.clone(),
+                }),
+            math: false,
+        },
+        notion::RichText::Mention {
+            mention,
+            annotations,
+            plain_text,
+            ..
+        } => {
+            let (content, url) = match mention {
+                notion::Mention::User { user } => {
+                    let name = user
+                        .name
+                        .as_deref()
+                        .unwrap_or(plain_text.trim_start_matches('@'));
+
+                    (format!("@{name}"), None)
+                }
+                notion::Mention::Page { page } => (
+                    plain_text.to_owned(),
+                    Some(options.link_resolver.page_url(&page.id)),
+                ),
+                notion::Mention::Database { database } => (
+                    plain_text.to_owned(),
+                    Some(options.link_resolver.database_url(&database.id)),
+                ),
+                notion::Mention::Date { date } => (format_date(date), None),
+                notion::Mention::LinkPreview { link_preview } => {
+                    (link_preview.url.clone(), Some(link_preview.url.clone()))
+                }
+                _ => (plain_text.to_owned(), None),
+            };
+
+            Run {
+                content,
+                annotations,
+                url,
+                math: false,
+            }
+        }
+        notion::RichText::Equation {
+            equation,
+            annotations,
+            ..
+        } => Run {
+            content: equation.expression.to_owned(),
+            annotations,
+            url: None,
+            math: true,
+        },
+    }
+}
+
+/// Wraps a run in the markers for its annotations and link. Surrounding
+/// whitespace is kept outside of the markers, as CommonMark doesn't treat
+/// `**bold **` as emphasis.
+fn render_run(run: &Run, options: &Options, context: Context, at_line_start: bool) -> String {
+    let trimmed = run.content.trim_start();
+    let leading = &run.content[..run.content.len() - trimmed.len()];
+    let core = trimmed.trim_end();
+    let trailing = &trimmed[core.len()..];
+
+    if core.is_empty() {
+        return run.content.clone();
+    }
+
+    let annotations = run.annotations;
+    // Text that is its own URL becomes an autolink, unless it's code which
+    // can't hold a link inside of it
+    let autolink = run.url.as_deref() == Some(core) && !annotations.code;
+
+    let context = match (context, &run.url) {
+        (Context::Text, Some(_)) => Context::LinkText,
+        (context, _) => context,
+    };
+
+    // Code goes innermost, as emphasis inside a code span is literal
+    let mut string = match &run.url {
+        Some(url) if autolink => format!("<{url}>"),
+        _ if run.math => match options.inline_math {
+            InlineMath::Dollars => match context {
+                // GFM splits table cells on pipes before parsing anything inline
+                Context::TableCell => format!("${}$", core.replace('|', "\\|")),
+                Context::Text | Context::LinkText => format!("${core}$"),
+            },
+            // The expression is parsed as Markdown like any other text, so its
+            // punctuation is escaped to reach MathJax as written. A single
+            // backslash before a parenthesis is a Markdown escape too, so the
+            // delimiters take two for MathJax to still see one.
+            InlineMath::Parentheses => {
+                format!("\\\\({}\\\\)", escape::escape_punctuation(core))
+            }
+        },
+        _ if annotations.code => escape::code_span(core, context),
+        _ => escape::escape(core, context, at_line_start || leading.contains('\n')),
+    };
+
+    if annotations.strikethrough {
+        string = format!("~~{string}~~");
+    }
+
+    if annotations.bold {
+        string = format!("**{string}**");
+    }
+
+    if annotations.italic {
+        string = format!("*{string}*");
+    }
+
+    if annotations.underline && options.underline == Underline::Html {
+        string = format!("<u>{string}</u>");
+    }
+
+    if options.colors == Colors::Span {
+        if let Some(style) = color_style(&annotations.color) {
+            string = format!(r#"<span style="{style}">{string}</span>"#);
+        }
+    }
+
+    if let Some(url) = run.url.as_deref().filter(|_| !autolink) {
+        string = format!("[{string}]({})", link_destination(url));
+    }
+
+    format!("{leading}{string}{trailing}")
+}
+
+pub fn convert_rich_text(text: &notion::RichText, options: &Options) -> String {
+    convert_rich_texts(std::slice::from_ref(text), options)
+}
+
+/// Converts a sequence of rich text elements, merging adjacent elements with
+/// identical formatting so they're wrapped once rather than as `**a****b**`.
+pub fn convert_rich_texts(texts: &[notion::RichText], options: &Options) -> String {
+    convert_rich_texts_in(texts, options, Context::Text)
+}
+
+/// Groups rich text elements into runs, merging adjacent elements with
+/// identical formatting.
+fn to_runs<'a>(texts: &'a [notion::RichText], options: &Options) -> Vec<Run<'a>> {
+    let mut runs: Vec<Run> = vec![];
+
+    for run in texts.iter().map(|text| to_run(text, options)) {
+        match runs.last_mut() {
+            // Equations are delimited one by one
+            Some(last)
+                if !last.math
+                    && !run.math
+                    && last.url == run.url
+                    && same_annotations(last.annotations, run.annotations) =>
+            {
+                last.content.push_str(&run.content);
+            }
+            _ => runs.push(run),
+        }
+    }
+
+    runs
+}
+
+/// Converts a sequence of rich text elements, escaping them for where they end
+/// up in the document.
+pub(crate) fn convert_rich_texts_in(
+    texts: &[notion::RichText],
+    options: &Options,
+    context: Context,
+) -> String {
+    let mut string = String::new();
+
+    for run in to_runs(texts, options).iter() {
+        // Only the start of a line can begin a block, ignoring indentation
+        let at_line_start = string
+            .rsplit('\n')
+            .next()
+            .is_none_or(|line| line.trim().is_empty());
+
+        string.push_str(&render_run(run, options, context, at_line_start));
+    }
+
+    string
+}
+
+/// Wraps a run in the inline HTML elements for its annotations and link, for
+/// places where Markdown isn't rendered such as inside HTML tables.
+fn render_run_html(run: &Run, options: &Options) -> String {
+    let annotations = run.annotations;
+
+    let mut string = if run.math {
+        escape::escape_html(&match options.inline_math {
+            InlineMath::Dollars => format!("${}$", run.content),
+            InlineMath::Parentheses => format!("\\({}\\)", run.content),
+        })
+    } else {
+        escape::escape_html(&run.content).replace('\n', "<br>")
+    };
+
+    if annotations.code {
+        string = format!("<code>{string}</code>");
+    }
+
+    if annotations.strikethrough {
+        string = format!("<s>{string}</s>");
+    }
+
+    if annotations.bold {
+        string = format!("<strong>{string}</strong>");
+    }
+
+    if annotations.italic {
+        string = format!("<em>{string}</em>");
+    }
+
+    if annotations.underline && options.underline == Underline::Html {
+        string = format!("<u>{string}</u>");
+    }
+
+    if options.colors == Colors::Span {
+        if let Some(style) = color_style(&annotations.color) {
+            string = format!(r#"<span style="{style}">{string}</span>"#);
+        }
+    }
+
+    if let Some(url) = &run.url {
+        string = format!(r#"<a href="{}">{string}</a>"#, escape::escape_html(url));
+    }
+
+    string
+}
+
+/// Converts a sequence of rich text elements to inline HTML.
+pub(crate) fn convert_rich_texts_html(texts: &[notion::RichText], options: &Options) -> String {
+    to_runs(texts, options)
+        .iter()
+        .map(|run| render_run_html(run, options))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(
+        content: &str,
+        annotations: notion::Annotations,
+        url: Option<&str>,
+    ) -> notion::RichText {
+        notion::RichText::Text {
+            text: notion::TextContent {
+                content: content.to_string(),
+                link: url.map(|url| notion::Link {
+                    url: url.to_string(),
+                }),
+            },
+            annotations,
+            plain_text: content.to_string(),
+            href: url.map(str::to_string),
+        }
+    }
+
+    fn plain(content: &str) -> notion::RichText {
+        text(content, Default::default(), None)
+    }
+
+    fn link(content: &str, url: &str) -> notion::RichText {
+        text(content, Default::default(), Some(url))
+    }
+
+    fn equation(expression: &str) -> notion::RichText {
+        notion::RichText::Equation {
+            equation: notion::Equation {
+                expression: expression.to_string(),
+            },
+            annotations: Default::default(),
+            plain_text: expression.to_string(),
+            href: None,
+        }
+    }
+
+    #[test]
+    fn links() {
+        let options = Options::default();
+
+        assert_eq!(
+            convert_rich_texts(
+                &[plain("see "), link("docs", "https://example.com")],
+                &options
+            ),
+            "see [docs](https://example.com)"
+        );
+        assert_eq!(
+            convert_rich_texts(&[link("a [b]", "https://example.com/a_(b)")], &options),
+            "[a \\[b\\]](<https://example.com/a_(b)>)"
+        );
+    }
+
+    #[test]
+    fn links_to_themselves_are_autolinks() {
+        let options = Options::default();
+        let code = notion::Annotations {
+            code: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            convert_rich_texts(
+                &[link("https://example.com", "https://example.com")],
+                &options
+            ),
+            "<https://example.com>"
+        );
+        // A code span can't hold an autolink, so it goes inside a link instead
+        assert_eq!(
+            convert_rich_texts(
+                &[text(
+                    "https://example.com",
+                    code,
+                    Some("https://example.com")
+                )],
+                &options
+            ),
+            "[`https://example.com`](https://example.com)"
+        );
+    }
+
+    #[test]
+    fn annotations_nest_inside_links() {
+        let options = Options {
+            colors: Colors::Span,
+            ..Options::default()
+        };
+        let all = notion::Annotations {
+            bold: true,
+            italic: true,
+            strikethrough: true,
+            underline: true,
+            code: true,
+            color: notion::Color::Red,
+        };
+
+        assert_eq!(
+            convert_rich_texts(&[text("a", all, Some("https://example.com"))], &options),
+            r#"[<span style="color: red"><u>***~~`a`~~***</u></span>](https://example.com)"#
+        );
+    }
+
+    #[test]
+    fn annotations_follow_options() {
+        let options = Options {
+            underline: Underline::Ignore,
+            ..Options::default()
+        };
+        let annotations = notion::Annotations {
+            underline: true,
+            color: notion::Color::BlueBackground,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            convert_rich_texts(&[text("a", annotations, None)], &options),
+            "a"
+        );
+    }
+
+    #[test]
+    fn whitespace_stays_outside_markers() {
+        let options = Options::default();
+        let bold = notion::Annotations {
+            bold: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            convert_rich_texts(
+                &[plain("a"), text(" b ", bold.clone(), None), plain("c")],
+                &options
+            ),
+            "a **b** c"
+        );
+        assert_eq!(
+            convert_rich_texts(&[plain("a"), text("  ", bold, None)], &options),
+            "a  "
+        );
+    }
+
+    #[test]
+    fn identical_runs_are_merged() {
+        let options = Options::default();
+        let bold = notion::Annotations {
+            bold: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            convert_rich_texts(
+                &[text("a", bold.clone(), None), text("b", bold.clone(), None)],
+                &options
+            ),
+            "**ab**"
+        );
+        // Runs with different links stay apart, as do equations
+        assert_eq!(
+            convert_rich_texts(
+                &[
+                    link("a", "https://a.example"),
+                    link("b", "https://b.example")
+                ],
+                &options
+            ),
+            "[a](https://a.example)[b](https://b.example)"
+        );
+        assert_eq!(to_runs(&[equation("x"), equation("y")], &options).len(), 2);
+        assert_eq!(
+            to_runs(&[text("a", bold, None), plain("b")], &options).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn parenthesized_math_survives_markdown() {
+        let options = Options {
+            inline_math: InlineMath::Parentheses,
+            ..Options::default()
+        };
+
+        assert_eq!(
+            convert_rich_texts(&[equation("\\{x\\}")], &options),
+            "\\\\(\\\\\\{x\\\\\\}\\\\)"
+        );
+        assert_eq!(
+            convert_rich_texts(&[equation("a \\\\ b")], &options),
+            "\\\\(a \\\\\\\\ b\\\\)"
+        );
+        assert_eq!(
+            convert_rich_texts(&[equation("a*b*c")], &options),
+            "\\\\(a\\*b\\*c\\\\)"
+        );
+        assert_eq!(
+            convert_rich_texts_in(&[equation("|x|")], &options, Context::TableCell),
+            "\\\\(\\|x\\|\\\\)"
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use crate::escape::Context;
+use crate::options::Options;
+use crate::rich_text::{convert_rich_texts_html, convert_rich_texts_in, plain_text};
+
+/// Renders rows of already converted cells as a GFM pipe table, padding short
+/// rows so every row has the same number of columns.
+pub(crate) fn pipe_table(rows: &[Vec<String>], has_column_header: bool) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+    if columns == 0 {
+        return String::new();
+    }
+
+    let row = |cells: &[String]| {
+        let cells = (0..columns)
+            .map(|column| cells.get(column).map_or("", String::as_str))
+            .collect::<Vec<&str>>();
+
+        format!("| {} |", cells.join(" | "))
+    };
+
+    // GFM tables always start with a header row, which is left empty when the
+    // table doesn't have one
+    let (header, body) = match rows.split_first() {
+        Some((header, body)) if has_column_header => (header.as_slice(), body),
+        _ => (&[][..], rows),
+    };
+
+    let mut lines = vec![row(header), format!("|{}", " --- |".repeat(columns))];
+    lines.extend(body.iter().map(Vec::as_slice).map(&row));
+
+    lines.join("\n")
+}
+
+/// Renders rows of cells as an HTML table, for content a pipe table can't
+/// hold such as line breaks.
+fn html_table(
+    rows: &[Vec<Vec<notion::RichText>>],
+    has_column_header: bool,
+    has_row_header: bool,
+    options: &Options,
+) -> String {
+    let mut lines = vec!["<table>".to_string()];
+
+    for (index, cells) in rows.iter().enumerate() {
+        let cells = cells
+            .iter()
+            .enumerate()
+            .map(|(column, cell)| {
+                let content = convert_rich_texts_html(cell, options);
+
+                if index == 0 && has_column_header {
+                    format!("<th>{content}</th>")
+                } else if column == 0 && has_row_header {
+                    format!(r#"<th scope="row">{content}</th>"#)
+                } else {
+                    format!("<td>{content}</td>")
+                }
+            })
+            .collect::<String>();
+
+        lines.push(format!("<tr>{cells}</tr>"));
+    }
+
+    lines.push("</table>".to_string());
+
+    lines.join("\n")
+}
+
+/// Converts the rows of a Notion table to a GFM pipe table, falling back to an
+/// HTML table when a cell contains line breaks.
+pub fn convert_table(
+    rows: &[Vec<Vec<notion::RichText>>],
+    has_column_header: bool,
+    has_row_header: bool,
+    options: &Options,
+) -> String {
+    let multiline = rows
+        .iter()
+        .flatten()
+        .flatten()
+        .any(|text| plain_text(text).contains('\n'));
+
+    if multiline {
+        return html_table(rows, has_column_header, has_row_header, options);
+    }
+
+    let rows = rows
+        .iter()
+        .enumerate()
+        .map(|(index, cells)| {
+            cells
+                .iter()
+                .enumerate()
+                .map(|(column, cell)| {
+                    let content = convert_rich_texts_in(cell, options, Context::TableCell);
+
+                    // Pipe tables have no header column, so it's set in bold
+                    let is_header = column == 0 && has_row_header;
+                    if is_header && !(index == 0 && has_column_header) && !content.is_empty() {
+                        format!("**{content}**")
+                    } else {
+                        content
+                    }
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<String>>>();
+
+    pipe_table(&rows, has_column_header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(content: &str) -> Vec<notion::RichText> {
+        vec![notion::RichText::Text {
+            text: notion::TextContent {
+                content: content.to_string(),
+                link: None,
+            },
+            annotations: Default::default(),
+            plain_text: content.to_string(),
+            href: None,
+        }]
+    }
+
+    fn strings(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|cells| cells.iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn pipe_tables_pad_short_rows() {
+        assert_eq!(
+            pipe_table(&strings(&[&["a", "b"], &["c"]]), true),
+            "| a | b |\n| --- | --- |\n| c |  |"
+        );
+        assert_eq!(pipe_table(&[], true), "");
+    }
+
+    #[test]
+    fn pipe_tables_without_a_header_get_an_empty_one() {
+        assert_eq!(
+            pipe_table(&strings(&[&["a", "b"]]), false),
+            "|  |  |\n| --- | --- |\n| a | b |"
+        );
+    }
+
+    #[test]
+    fn row_headers_are_bold() {
+        let rows = vec![
+            vec![cell("Name"), cell("Value")],
+            vec![cell("a|b"), cell("1")],
+            vec![cell(""), cell("2")],
+        ];
+
+        assert_eq!(
+            convert_table(&rows, true, true, &Options::default()),
+            "| Name | Value |\n| --- | --- |\n| **a\\|b** | 1 |\n|  | 2 |"
+        );
+    }
+
+    #[test]
+    fn line_breaks_fall_back_to_html() {
+        let rows = vec![vec![cell("a"), cell("b\nc")], vec![cell("<d>"), cell("e")]];
+
+        assert_eq!(
+            convert_table(&rows, false, true, &Options::default()),
+            "<table>\n\
+             <tr><th scope=\"row\">a</th><td>b<br>c</td></tr>\n\
+             <tr><th scope=\"row\">&lt;d&gt;</th><td>e</td></tr>\n\
+             </table>"
+        );
+    }
+}